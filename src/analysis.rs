@@ -0,0 +1,1581 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Functions for inspecting the models, useful when writing assertions
+
+use crate::geometry::{
+    angle_difference, azimuth, loop_points, point_in_ring, polygon_centroid, polygon_loops,
+    project, segments_cross,
+};
+use crate::modify::surface_index;
+use crate::Float;
+use geometry3d::{Point3D, Polygon3D, Vector3D};
+use simple_model::{
+    substance::Substance, Boundary, Construction, Infiltration, Material, SimpleModel, Surface,
+};
+use std::rc::Rc;
+
+/// The density of air assumed by these helpers, in kg/m3
+pub(crate) const AIR_DENSITY: Float = 1.2;
+
+/// The specific heat capacity of air assumed by these helpers, in J/kgK
+pub(crate) const AIR_SPECIFIC_HEAT: Float = 1005.;
+
+/// The thermal resistance assumed for gas cavities, in m2K/W. This is the
+/// usual value for an unventilated air layer, regardless of its thickness.
+pub(crate) const GAS_CAVITY_RESISTANCE: Float = 0.18;
+
+/// Gets a property of a [`Substance::Normal`], which the test models always set
+macro_rules! normal_property {
+    ($substance:expr, $property:ident) => {
+        match $substance {
+            Substance::Normal(s) => s
+                .$property()
+                .expect(concat!("Substance has no ", stringify!($property))),
+            _ => panic!("Expecting a Normal substance"),
+        }
+    };
+}
+
+/// Calculates the thermal resistance of a [`Material`], in m2K/W.
+///
+/// Gas layers are assumed to have [`GAS_CAVITY_RESISTANCE`].
+pub(crate) fn material_resistance(material: &Material) -> Float {
+    match &*material.substance {
+        Substance::Gas(_) => GAS_CAVITY_RESISTANCE,
+        s => material.thickness / normal_property!(s, thermal_conductivity),
+    }
+}
+
+/// Calculates the heat capacity of a [`Material`] per unit of area, in J/m2K
+///
+/// Gas layers are assumed to be air.
+pub(crate) fn material_areal_capacitance(material: &Material) -> Float {
+    match &*material.substance {
+        Substance::Gas(_) => AIR_DENSITY * AIR_SPECIFIC_HEAT * material.thickness,
+        s => {
+            normal_property!(s, density)
+                * normal_property!(s, specific_heat_capacity)
+                * material.thickness
+        }
+    }
+}
+
+/// Calculates the thermal resistance of a [`Construction`] (without air films), in m2K/W
+pub(crate) fn construction_resistance(construction: &Construction) -> Float {
+    construction
+        .materials
+        .iter()
+        .map(|m| material_resistance(m))
+        .sum()
+}
+
+/// Calculates the heat capacity of a [`Construction`] per unit of area, in J/m2K
+pub(crate) fn construction_areal_capacitance(construction: &Construction) -> Float {
+    construction
+        .materials
+        .iter()
+        .map(|m| material_areal_capacitance(m))
+        .sum()
+}
+
+/// The interior air film resistance assumed by [`construction_u_value`], in m2K/W
+pub(crate) const INTERIOR_FILM_RESISTANCE: Float = 0.13;
+
+/// The exterior air film resistance assumed by [`construction_u_value`], in m2K/W
+pub(crate) const EXTERIOR_FILM_RESISTANCE: Float = 0.04;
+
+/// Finds a construction in the model, by name
+fn construction_by_name<'a>(
+    model: &'a SimpleModel,
+    construction_name: &str,
+) -> Result<&'a Rc<Construction>, String> {
+    model
+        .constructions
+        .iter()
+        .find(|c| c.name == construction_name)
+        .ok_or_else(|| format!("Could not find construction '{}'", construction_name))
+}
+
+/// Calculates the thermal resistance (i.e., R-value) of the construction
+/// called `construction_name`, in m2K/W.
+///
+/// This is the sum of `thickness / conductivity` over its layers, without air
+/// films. Gas layers are assumed to have a resistance of `0.18` m2K/W.
+pub fn construction_r_value(model: &SimpleModel, construction_name: &str) -> Result<Float, String> {
+    let construction = construction_by_name(model, construction_name)?;
+    Ok(construction_resistance(construction))
+}
+
+/// Calculates the thermal transmittance (i.e., U-value) of the construction
+/// called `construction_name`, in W/m2K.
+///
+/// If `include_films`, the interior (`0.13` m2K/W) and exterior (`0.04` m2K/W)
+/// air film resistances are added to [`construction_r_value`] before
+/// inverting it. Otherwise, this is simply `1 / R`.
+pub fn construction_u_value(
+    model: &SimpleModel,
+    construction_name: &str,
+    include_films: bool,
+) -> Result<Float, String> {
+    let mut r = construction_r_value(model, construction_name)?;
+    if include_films {
+        r += INTERIOR_FILM_RESISTANCE + EXTERIOR_FILM_RESISTANCE;
+    }
+    Ok(1. / r)
+}
+
+/// Counts how many of a pair of boundaries are spaces
+fn n_spaces(front: Result<&Boundary, String>, back: Result<&Boundary, String>) -> usize {
+    [front, back]
+        .iter()
+        .filter(|b| matches!(b, Ok(Boundary::Space(_))))
+        .count()
+}
+
+/// Checks whether a pair of boundaries separates a space from the outdoors
+/// or the ground (i.e., the heat flowing through it is lost from the model).
+///
+/// Partitions have spaces on both sides, and free-standing shading
+/// surfaces (e.g., a porch or a neighbouring building) have none.
+fn is_envelope(front: Result<&Boundary, String>, back: Result<&Boundary, String>) -> bool {
+    n_spaces(front, back) == 1
+}
+
+/// Returns the surfaces whose outward normal has an azimuth within
+/// `tolerance` degrees of `azimuth` (measured clockwise from North, so
+/// South is `180`).
+///
+/// Horizontal surfaces (e.g., floors and roofs) have no azimuth, so they
+/// are never returned.
+pub fn surfaces_facing(model: &SimpleModel, azimuth: Float, tolerance: Float) -> Vec<Rc<Surface>> {
+    model
+        .surfaces
+        .iter()
+        .filter(|s| {
+            let normal = s.vertices.normal();
+            if normal.z.abs() > 0.999 {
+                return false;
+            }
+            angle_difference(self::azimuth(normal), azimuth) <= tolerance
+        })
+        .map(Rc::clone)
+        .collect()
+}
+
+/// Returns the centroid and the (unit, outward) normal of the surface called
+/// `surface_name`, e.g., for placing sensors or checking orientations.
+///
+/// The centroid is the centre of area of the surface, so it accounts for
+/// any window cut out of it.
+pub fn surface_geometry(
+    model: &SimpleModel,
+    surface_name: &str,
+) -> Result<(Point3D, Vector3D), String> {
+    let index = surface_index(model, surface_name)?;
+    let polygon = &model.surfaces[index].vertices;
+    Ok((polygon_centroid(polygon), polygon.normal()))
+}
+
+/// Finds the main wall of [`get_single_zone_test_building`](crate::get_single_zone_test_building)
+/// (i.e., the South-facing "Surface" in which the window is cut) by name, so tests do not
+/// depend on the order in which the surfaces were added.
+///
+/// When the wall is bermed, this is the upper segment. Models without such a
+/// wall (e.g., most presets, or zones with a `name_prefix`) produce an error.
+pub fn main_wall(model: &SimpleModel) -> Result<Rc<Surface>, String> {
+    let index = surface_index(model, "Surface")?;
+    Ok(Rc::clone(&model.surfaces[index]))
+}
+
+/// Calculates the net opaque area (in m2) of the surface called `surface_name`: the
+/// area of its outer loop minus that of every opening (i.e., hole) cut into it, which
+/// is the area that matters for conduction.
+pub fn net_opaque_area(model: &SimpleModel, surface_name: &str) -> Result<Float, String> {
+    let index = surface_index(model, surface_name)?;
+    let loop_area = |the_loop: &geometry3d::Loop3D| -> Result<Float, String> {
+        Ok(Polygon3D::new(the_loop.clone())?.area())
+    };
+    let polygon = &model.surfaces[index].vertices;
+    let mut area = loop_area(polygon.outer())?;
+    for hole in polygon.inner() {
+        area -= loop_area(hole)?;
+    }
+    Ok(area)
+}
+
+/// Calculates the axis-aligned bounding box of the model, returning its
+/// minimum and maximum corners.
+///
+/// This considers the vertices of every surface (fenestrations sit within
+/// them). The model is expected to have at least one surface.
+pub fn bounding_box(model: &SimpleModel) -> (Point3D, Point3D) {
+    assert!(
+        !model.surfaces.is_empty(),
+        "Cannot calculate the bounding box of a model without surfaces"
+    );
+    let mut min = Point3D::new(Float::MAX, Float::MAX, Float::MAX);
+    let mut max = Point3D::new(Float::MIN, Float::MIN, Float::MIN);
+    for p in model
+        .surfaces
+        .iter()
+        .flat_map(|s| loop_points(s.vertices.outer()))
+    {
+        min = Point3D::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+        max = Point3D::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+    }
+    (min, max)
+}
+
+/// Estimates the SHGC of a window construction (see [`shgc_area_by_orientation`]).
+/// Gas layers are transparent, and layers with no solar transmittance are opaque.
+fn construction_shgc(construction: &Construction) -> Float {
+    construction
+        .materials
+        .iter()
+        .map(|m| match &*m.substance {
+            Substance::Normal(s) => s.solar_transmittance().unwrap_or(0.0),
+            _ => 1.0,
+        })
+        .product()
+}
+
+/// Adds up the area times the SHGC of the windows of each facade, for cooling-load
+/// screening. The result is a list of `(azimuth, sum of area * SHGC)`, sorted by azimuth.
+///
+/// The azimuth of each window is that of its normal, measured clockwise from North
+/// (so South is `180`) and rounded to whole degrees; horizontal windows (e.g., skylights)
+/// have no azimuth, so they are left out. The SHGC is estimated as the product of the solar
+/// transmittances of the layers of the window (which is exact for a single pane with no
+/// absorbtance, and ignores the absorbed fraction that flows inwards otherwise).
+pub fn shgc_area_by_orientation(model: &SimpleModel) -> Vec<(Float, Float)> {
+    group_by_azimuth(
+        model
+            .fenestrations
+            .iter()
+            .filter(|f| f.vertices.normal().z.abs() <= 0.999)
+            .map(|f| {
+                let gain = f.area() * construction_shgc(&f.construction);
+                (f.vertices.normal(), gain)
+            }),
+    )
+}
+
+/// Adds up the values that share the azimuth of their (non-horizontal) normal, rounded
+/// to whole degrees, returning `(azimuth, total)` sorted by azimuth
+fn group_by_azimuth<I: Iterator<Item = (Vector3D, Float)>>(items: I) -> Vec<(Float, Float)> {
+    let mut ret: Vec<(Float, Float)> = Vec::new();
+    for (normal, value) in items {
+        let a = azimuth(normal).round() % 360.;
+        match ret.iter_mut().find(|(b, _)| (*b - a).abs() < 0.5) {
+            Some((_, total)) => *total += value,
+            None => ret.push((a, value)),
+        }
+    }
+    ret.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    ret
+}
+
+/// Adds up the gross area (i.e., including windows) of the exterior walls of each facade,
+/// for checking window-to-wall ratios. The result is a list of `(azimuth, total gross area)`,
+/// sorted by azimuth as in [`shgc_area_by_orientation`].
+///
+/// Only vertical surfaces with a space on one side (and not on the other) are
+/// included, so partitions, roofs, floors and free-standing shading surfaces are left out.
+pub fn wall_area_by_orientation(model: &SimpleModel) -> Vec<(Float, Float)> {
+    let is_space = |b: Result<&Boundary, String>| matches!(b, Ok(Boundary::Space(_)));
+    group_by_azimuth(
+        model
+            .surfaces
+            .iter()
+            .filter(|s| is_space(s.front_boundary()) != is_space(s.back_boundary()))
+            .filter(|s| s.vertices.normal().z.abs() < 1e-3)
+            .map(|s| {
+                let gross = Polygon3D::new(s.vertices.outer().clone()).unwrap().area();
+                (s.vertices.normal(), gross)
+            }),
+    )
+}
+
+/// Calculates the glazed area that faces the sun (in m2), for hand-checking
+/// solar gains.
+///
+/// This is the sum, over all fenestrations, of their area times the cosine
+/// of the angle of incidence of the sun (or zero, if the sun is behind them).
+/// The `solar_azimuth` is measured clockwise from North (so South is `180`)
+/// and the `solar_altitude` is measured up from the horizon, both in degrees.
+pub fn solar_aperture(model: &SimpleModel, solar_azimuth: Float, solar_altitude: Float) -> Float {
+    let (azimuth, altitude) = (solar_azimuth.to_radians(), solar_altitude.to_radians());
+    let sun = Vector3D::new(
+        azimuth.sin() * altitude.cos(),
+        azimuth.cos() * altitude.cos(),
+        altitude.sin(),
+    );
+    model
+        .fenestrations
+        .iter()
+        .map(|f| f.area() * (f.vertices.normal() * sun).max(0.0))
+        .sum()
+}
+
+/// Lists the name and nominal power (in W) of every load in the model.
+///
+/// This walks the luminaires (using their `max_power`).
+/// HVACs are not listed: an [`ElectricHeater`](simple_model::hvac::ElectricHeater) does not store a nominal
+/// power in `simple_model` (its power lives in the simulation state), and it is not an
+/// internal gain (see [`total_internal_gains`]).
+pub fn loads_summary(model: &SimpleModel) -> Vec<(String, Float)> {
+    model
+        .luminaires
+        .iter()
+        .map(|l| {
+            let power = l
+                .max_power()
+                .expect("Luminaires in the test models always have a max_power");
+            (l.name.clone(), power)
+        })
+        .collect()
+}
+
+/// Adds up the nominal power (in W) of every internal gain in the model, that is,
+/// the total of [`loads_summary`].
+///
+/// This is the whole power of each load, regardless of how it splits into
+/// convection and radiation. Since `simple_model` has no equipment or
+/// occupancy loads, only luminaires are counted.
+pub fn total_internal_gains(model: &SimpleModel) -> Float {
+    loads_summary(model).iter().map(|(_, power)| power).sum()
+}
+
+/// Calculates the overall heat loss coefficient (i.e., UA) of the model, in W/K.
+///
+/// This adds up `area / R` for every surface and fenestration that has a space on
+/// exactly one side (so interior partitions and free-standing shading surfaces are left out), plus
+/// `density * cp * rate` for the constant infiltration of each space.
+///
+/// # Assumptions
+/// * Air films are not included, only the layers of the constructions
+/// * Gas layers have a resistance of `0.18` m2K/W
+/// * Air has a density of `1.2` kg/m3 and a specific heat of `1005` J/kgK
+pub fn total_ua(model: &SimpleModel) -> Float {
+    let surfaces: Float = model
+        .surfaces
+        .iter()
+        .filter(|s| is_envelope(s.front_boundary(), s.back_boundary()))
+        .map(|s| s.area() / construction_resistance(&s.construction))
+        .sum();
+
+    let fenestrations: Float = model
+        .fenestrations
+        .iter()
+        .filter(|s| is_envelope(s.front_boundary(), s.back_boundary()))
+        .map(|s| s.area() / construction_resistance(&s.construction))
+        .sum();
+
+    let infiltration: Float = model
+        .spaces
+        .iter()
+        .map(|s| match s.infiltration() {
+            Ok(Infiltration::Constant(rate)) => AIR_DENSITY * AIR_SPECIFIC_HEAT * rate,
+            _ => 0.0,
+        })
+        .sum();
+
+    surfaces + fenestrations + infiltration
+}
+
+/// Calculates the steady-state air temperature (in C) of a model in which
+/// `heater_power` W are delivered to the air and the outdoors are at `outdoor_temp`,
+/// from the energy balance `heater_power = total_ua * (T - outdoor_temp)`.
+///
+/// This shares the assumptions of [`total_ua`] (e.g., no air films, and infiltration
+/// air at the outdoor temperature), and it ignores solar and internal gains.
+pub fn analytical_steady_state_temperature(
+    model: &SimpleModel,
+    outdoor_temp: Float,
+    heater_power: Float,
+) -> Float {
+    let ua = total_ua(model);
+    assert!(
+        ua > 0.0,
+        "The model needs some heat losses to reach a steady state"
+    );
+    outdoor_temp + heater_power / ua
+}
+
+/// Estimates the dominant thermal time constant of the model (in seconds) as its
+/// total heat capacity divided by its [`total_ua`].
+///
+/// The heat capacity is the sum of `density * cp * thickness * area` over the
+/// layers of every surface and fenestration attached to a space (including partitions, but
+/// not free-standing shading surfaces) plus
+/// `density * cp * volume` of the air in every space. This is a single-node
+/// (i.e., lumped) estimate, so it ignores how the mass is distributed, and
+/// it makes the same assumptions as [`total_ua`]. Models with no heat losses (e.g.,
+/// [`get_adiabatic_box`](crate::get_adiabatic_box)) panic.
+pub fn thermal_time_constant(model: &SimpleModel) -> Float {
+    let surfaces: Float = model
+        .surfaces
+        .iter()
+        .filter(|s| n_spaces(s.front_boundary(), s.back_boundary()) > 0)
+        .map(|s| s.area() * construction_areal_capacitance(&s.construction))
+        .sum();
+    let fenestrations: Float = model
+        .fenestrations
+        .iter()
+        .filter(|s| n_spaces(s.front_boundary(), s.back_boundary()) > 0)
+        .map(|s| s.area() * construction_areal_capacitance(&s.construction))
+        .sum();
+    let air: Float = model
+        .spaces
+        .iter()
+        .map(|s| AIR_DENSITY * AIR_SPECIFIC_HEAT * s.volume().expect("Space has no volume"))
+        .sum();
+
+    let ua = total_ua(model);
+    assert!(ua > 0.0, "A model with no heat losses has no time constant");
+    (surfaces + fenestrations + air) / ua
+}
+
+/// Checks that a polygon is valid, describing the problems found (if any)
+fn check_polygon(name: &str, polygon: &Polygon3D, problems: &mut Vec<String>) {
+    let normal = polygon.normal();
+    if polygon.area() <= 0.0 || normal.length() < 0.5 {
+        problems.push(format!("'{}' has no area", name));
+        return;
+    }
+
+    for (loop_index, the_loop) in polygon_loops(polygon).iter().enumerate() {
+        let points = loop_points(the_loop);
+        if points.len() < 3 {
+            problems.push(format!(
+                "Loop {} of '{}' has less than 3 vertices",
+                loop_index, name
+            ));
+            continue;
+        }
+
+        // Planar
+        let origin = points[0];
+        if points.iter().any(|p| ((*p - origin) * normal).abs() > 1e-3) {
+            problems.push(format!("Loop {} of '{}' is not planar", loop_index, name));
+        }
+
+        // Non self-intersecting
+        let flat = project(&points, normal);
+        let n = flat.len();
+        'outer: for i in 0..n {
+            for j in i + 2..n {
+                if i == 0 && j == n - 1 {
+                    // These edges are adjacent
+                    continue;
+                }
+                if segments_cross(flat[i], flat[(i + 1) % n], flat[j], flat[(j + 1) % n]) {
+                    problems.push(format!(
+                        "Loop {} of '{}' intersects itself",
+                        loop_index, name
+                    ));
+                    break 'outer;
+                }
+            }
+        }
+    }
+}
+
+/// Checks that the polygons of every surface and fenestration in the model are
+/// planar, have an area and do not intersect themselves, reporting the problems by name.
+///
+/// Closure is not checked because a `Polygon3D` can only be built out of
+/// closed loops.
+pub fn validate_geometry(model: &SimpleModel) -> Result<(), Vec<String>> {
+    let mut problems = Vec::new();
+    for s in model.surfaces.iter() {
+        check_polygon(&s.name, &s.vertices, &mut problems);
+    }
+    for f in model.fenestrations.iter() {
+        check_polygon(&f.name, &f.vertices, &mut problems);
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Checks whether every point in `points` lies on the plane of `polygon`
+fn is_coplanar(polygon: &Polygon3D, points: &[Point3D]) -> bool {
+    let normal = polygon.normal();
+    let origin = polygon.outer()[0];
+    points.iter().all(|p| ((*p - origin) * normal).abs() < 1e-3)
+}
+
+/// Checks whether `the_loop` has the same vertices as `points`
+fn same_vertices(the_loop: &geometry3d::Loop3D, points: &[Point3D]) -> bool {
+    let vertices = loop_points(the_loop);
+    vertices.len() == points.len()
+        && points
+            .iter()
+            .all(|p| vertices.iter().any(|v| v.distance(*p) < 1e-6))
+}
+
+/// Checks whether the loop made of `points` lies within the outer loop of `polygon` (i.e.,
+/// ignoring its holes), assuming they are coplanar
+fn is_contained(polygon: &Polygon3D, points: &[Point3D]) -> bool {
+    let normal = polygon.normal();
+    let host = project(&loop_points(polygon.outer()), normal);
+    let inner = project(points, normal);
+    let (n, m) = (host.len(), inner.len());
+    inner.iter().all(|p| point_in_ring(*p, &host))
+        && (0..m).all(|i| {
+            (0..n)
+                .all(|j| !segments_cross(inner[i], inner[(i + 1) % m], host[j], host[(j + 1) % n]))
+        })
+}
+
+/// Checks that every fenestration lies within its host surface, reporting the
+/// offenders by name.
+///
+/// The host is the surface with a hole that matches the fenestration or, if
+/// there is none, any surface on the same plane. Fenestrations are compared against the
+/// outer loop of the host (i.e., the wall before the openings were cut), so
+/// they fail if they extend beyond its edges or if no surface hosts them.
+pub fn validate_openings(model: &SimpleModel) -> Result<(), Vec<String>> {
+    let mut problems = Vec::new();
+    for f in model.fenestrations.iter() {
+        let points = loop_points(f.vertices.outer());
+        let hosts: Vec<&Rc<Surface>> = model
+            .surfaces
+            .iter()
+            .filter(|s| (s.vertices.normal() * f.vertices.normal()).abs() > 0.999)
+            .filter(|s| is_coplanar(&s.vertices, &points))
+            .collect();
+        let with_hole: Vec<&&Rc<Surface>> = hosts
+            .iter()
+            .filter(|s| s.vertices.inner().iter().any(|h| same_vertices(h, &points)))
+            .collect();
+
+        let (candidates, kind) = if with_hole.is_empty() {
+            (hosts, "any surface on its plane")
+        } else {
+            (with_hole.into_iter().copied().collect(), "its host surface")
+        };
+        if candidates.is_empty() {
+            problems.push(format!("'{}' does not lie on any surface", f.name));
+        } else if !candidates
+            .iter()
+            .any(|s| is_contained(&s.vertices, &points))
+        {
+            problems.push(format!("'{}' extends beyond {}", f.name, kind));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Joins a list of `items` into a single line, describing each of them
+/// with `f`
+fn describe_list<T, F: Fn(&T) -> String>(kind: &str, items: &[T], f: F) -> String {
+    let descriptions: Vec<String> = items.iter().map(f).collect();
+    format!("{} {}(s): {}", items.len(), kind, descriptions.join(", "))
+}
+
+/// Produces a compact, human-readable summary of the model: its spaces
+/// (with volumes), surfaces and fenestrations (with areas), constructions
+/// (with their layers) and loads (see [`loads_summary`]).
+pub fn describe_model(model: &SimpleModel) -> String {
+    let lines = [
+        format!("Model '{}'", model.name),
+        describe_list("space", &model.spaces, |s| match s.volume() {
+            Ok(v) => format!("{} ({:.2} m3)", s.name, v),
+            Err(_) => s.name.clone(),
+        }),
+        describe_list("surface", &model.surfaces, |s| {
+            format!("{} ({:.2} m2)", s.name, s.area())
+        }),
+        describe_list("fenestration", &model.fenestrations, |s| {
+            format!("{} ({:.2} m2)", s.name, s.area())
+        }),
+        describe_list("construction", &model.constructions, |c| {
+            let layers: Vec<&str> = c.materials.iter().map(|m| m.name.as_str()).collect();
+            format!("{} [{}]", c.name, layers.join(", "))
+        }),
+        describe_list("load", &loads_summary(model), |(name, power)| {
+            format!("{} ({:.2} W)", name, power)
+        }),
+        format!("{} HVAC(s)", model.hvacs.len()),
+    ];
+    lines.join("\n")
+}
+
+/// Describes a boundary as `"Outdoor"` (i.e., not set), `"Ground"` or
+/// `"Space(name)"`.
+pub(crate) fn boundary_name(boundary: Result<&Boundary, String>) -> String {
+    match boundary {
+        Err(_) => "Outdoor".to_string(),
+        Ok(Boundary::Ground) => "Ground".to_string(),
+        Ok(Boundary::Space(s)) => format!("Space({})", s.name),
+    }
+}
+
+/// Lists every surface and then every fenestration in the model as
+/// `(name, front boundary, back boundary)`.
+///
+/// Boundaries are described as `"Outdoor"` (i.e., not set), `"Ground"` or
+/// `"Space(name)"`, which is handy for debugging the connectivity of
+/// multi-zone models.
+pub fn boundary_report(model: &SimpleModel) -> Vec<(String, String, String)> {
+    let surfaces = model.surfaces.iter().map(|s| {
+        (
+            s.name.clone(),
+            boundary_name(s.front_boundary()),
+            boundary_name(s.back_boundary()),
+        )
+    });
+    let fenestrations = model.fenestrations.iter().map(|s| {
+        (
+            s.name.clone(),
+            boundary_name(s.front_boundary()),
+            boundary_name(s.back_boundary()),
+        )
+    });
+    surfaces.chain(fenestrations).collect()
+}
+
+/// Lists every material in the model with the number of constructions that
+/// reference it (i.e., the same `Rc`, not just a material with the same name).
+///
+/// This is handy for checking that presets share materials instead of
+/// duplicating them. A construction that uses a material in several layers
+/// counts once.
+pub fn shared_materials(model: &SimpleModel) -> Vec<(String, usize)> {
+    model
+        .materials
+        .iter()
+        .map(|material| {
+            let count = model
+                .constructions
+                .iter()
+                .filter(|c| c.materials.iter().any(|m| Rc::ptr_eq(m, material)))
+                .count();
+            (material.name.clone(), count)
+        })
+        .collect()
+}
+
+/// The geometric inputs of rule-of-thumb daylight-factor formulas for
+/// one window. See [`daylight_geometry`].
+#[derive(Clone, Debug)]
+pub struct DaylightGeom {
+    /// The name of the fenestration
+    pub name: String,
+
+    /// The height of the top of the window above `z = 0` (i.e., the ground), in m
+    pub window_head_height: Float,
+
+    /// How far the room extends away from the window, in m
+    pub room_depth: Float,
+
+    /// The area of the window, in m2
+    pub glazed_area: Float,
+}
+
+/// Collects the [`DaylightGeom`] of every fenestration in the model.
+///
+/// The room is the space on either side of the window (the front one, if
+/// both are spaces), and its depth is the largest distance between the plane of the window
+/// and any vertex of the surfaces that bound that space. Windows that face no space
+/// have no room, so their depth is zero, as is the depth of a room that is only bound by the
+/// wall of its window (e.g., [`get_single_zone_test_building`](crate::get_single_zone_test_building)).
+pub fn daylight_geometry(model: &SimpleModel) -> Vec<DaylightGeom> {
+    let space_of = |boundary: Result<&Boundary, String>| match boundary {
+        Ok(Boundary::Space(s)) => Some(Rc::clone(s)),
+        _ => None,
+    };
+    model
+        .fenestrations
+        .iter()
+        .map(|f| {
+            let window = loop_points(f.vertices.outer());
+            let window_head_height = window.iter().map(|p| p.z).fold(Float::MIN, Float::max);
+
+            let normal = f.vertices.normal();
+            let room = space_of(f.front_boundary()).or_else(|| space_of(f.back_boundary()));
+            let room_depth = match room {
+                None => 0.0,
+                Some(room) => model
+                    .surfaces
+                    .iter()
+                    .filter(|s| {
+                        [s.front_boundary(), s.back_boundary()]
+                            .iter()
+                            .any(|b| matches!(b, Ok(Boundary::Space(x)) if Rc::ptr_eq(x, &room)))
+                    })
+                    .flat_map(|s| loop_points(s.vertices.outer()))
+                    .map(|p| ((p - window[0]) * normal).abs())
+                    .fold(0.0, Float::max),
+            };
+
+            DaylightGeom {
+                name: f.name.clone(),
+                window_head_height,
+                room_depth,
+                glazed_area: f.area(),
+            }
+        })
+        .collect()
+}
+
+/// Lists the [`Rc::strong_count`] of every space, substance, material, construction,
+/// surface and fenestration in the model (in that order), by name, for spotting
+/// unexpected shared ownership after composing or merging models.
+///
+/// The counts include the reference held by the model itself, so an object that nothing
+/// else uses has a count of `1`.
+pub fn rc_report(model: &SimpleModel) -> Vec<(String, usize)> {
+    fn counts<T, F: Fn(&T) -> String>(items: &[Rc<T>], name: F) -> Vec<(String, usize)> {
+        items
+            .iter()
+            .map(|x| (name(x), Rc::strong_count(x)))
+            .collect()
+    }
+    let mut ret = counts(&model.spaces, |x| x.name.clone());
+    ret.extend(counts(&model.substances, |x| match x {
+        Substance::Normal(s) => s.name.clone(),
+        Substance::Gas(s) => s.name.clone(),
+    }));
+    ret.extend(counts(&model.materials, |x| x.name.clone()));
+    ret.extend(counts(&model.constructions, |x| x.name.clone()));
+    ret.extend(counts(&model.surfaces, |x| x.name.clone()));
+    ret.extend(counts(&model.fenestrations, |x| x.name.clone()));
+    ret
+}
+
+/// Describes the layers of a construction by their physical properties
+fn construction_signature(construction: &Construction) -> String {
+    let layers: Vec<String> = construction
+        .materials
+        .iter()
+        .map(|m| match &*m.substance {
+            Substance::Gas(_) => format!("{:.4}m of gas", m.thickness),
+            s => format!(
+                "{:.4}m (k={:.4}, rho={:.4}, cp={:.4})",
+                m.thickness,
+                normal_property!(s, thermal_conductivity),
+                normal_property!(s, density),
+                normal_property!(s, specific_heat_capacity)
+            ),
+        })
+        .collect();
+    format!("[{}]", layers.join(", "))
+}
+
+/// Builds sorted descriptions of the elements of the model, so that
+/// models can be compared regardless of the order of their elements.
+fn model_signatures(model: &SimpleModel) -> Vec<(&'static str, Vec<String>)> {
+    let sorted = |mut list: Vec<String>| {
+        list.sort();
+        list
+    };
+
+    let spaces = model
+        .spaces
+        .iter()
+        .map(|s| match s.volume() {
+            Ok(v) => format!("'{}' of {:.4} m3", s.name, v),
+            Err(_) => format!("'{}' with no volume", s.name),
+        })
+        .collect();
+    let surfaces = model
+        .surfaces
+        .iter()
+        .map(|s| {
+            format!(
+                "'{}' of {:.4} m2, {} -> {}, {}",
+                s.name,
+                s.area(),
+                boundary_name(s.front_boundary()),
+                boundary_name(s.back_boundary()),
+                construction_signature(&s.construction)
+            )
+        })
+        .collect();
+    let fenestrations = model
+        .fenestrations
+        .iter()
+        .map(|s| {
+            format!(
+                "'{}' of {:.4} m2, {} -> {}, {}",
+                s.name,
+                s.area(),
+                boundary_name(s.front_boundary()),
+                boundary_name(s.back_boundary()),
+                construction_signature(&s.construction)
+            )
+        })
+        .collect();
+    let constructions = model
+        .constructions
+        .iter()
+        .map(|c| construction_signature(c))
+        .collect();
+    let loads = loads_summary(model)
+        .iter()
+        .map(|(name, power)| format!("'{}' of {:.4} W", name, power))
+        .collect();
+
+    vec![
+        ("space", sorted(spaces)),
+        ("surface", sorted(surfaces)),
+        ("fenestration", sorted(fenestrations)),
+        ("construction", sorted(constructions)),
+        ("load", sorted(loads)),
+        ("HVAC", vec![format!("{} HVAC(s)", model.hvacs.len())]),
+    ]
+}
+
+/// Checks whether two models are structurally equivalent: same spaces (by name
+/// and volume), surfaces and fenestrations (by name, area,
+/// boundaries and layers), constructions (by the properties of their layers)
+/// and loads. The order in which the elements were added to the models
+/// and the identity of the `Rc`s are ignored.
+///
+/// Returns a description of the first mismatch found.
+pub fn models_equivalent(a: &SimpleModel, b: &SimpleModel) -> Result<(), String> {
+    for ((kind, list_a), (_, list_b)) in model_signatures(a).iter().zip(model_signatures(b).iter())
+    {
+        if list_a.len() != list_b.len() {
+            return Err(format!(
+                "The first model has {} {}(s) and the second one has {}",
+                list_a.len(),
+                kind,
+                list_b.len()
+            ));
+        }
+        for (item_a, item_b) in list_a.iter().zip(list_b.iter()) {
+            if item_a != item_b {
+                return Err(format!("Mismatching {}: {} vs {}", kind, item_a, item_b));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use crate::{
+        get_building_with_buffer, get_core_perimeter_floor, get_courtyard_building,
+        get_deep_plan_room, get_single_zone_test_building, get_sunspace_building,
+        SingleZoneTestBuildingOptions, TestMat,
+    };
+    use simple_model::{substance::Normal, Fenestration, FenestrationPositions, FenestrationType};
+
+    #[test]
+    fn test_surfaces_facing() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 2.,
+            surface_height: 2.,
+            window_width: 1.,
+            window_height: 1.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+        let (model, _header) = get_single_zone_test_building(&options);
+        assert_eq!(surfaces_facing(&model, 180., 1.).len(), 1);
+        assert_eq!(surfaces_facing(&model, 0., 1.).len(), 0);
+
+        // Walls facing South are the outer South wall and the
+        // North wall of the courtyard
+        let (model, _header) = get_courtyard_building(
+            &SingleZoneTestBuildingOptions {
+                surface_width: 10.,
+                surface_height: 3.,
+                ..options
+            },
+            0.5,
+        );
+        let south = surfaces_facing(&model, 180., 1.);
+        assert_eq!(south.len(), 2);
+        assert!(south.iter().any(|s| s.name == "Outer wall South"));
+        assert!(south.iter().any(|s| s.name == "Courtyard wall North"));
+
+        // Wrap around North
+        assert_eq!(surfaces_facing(&model, 359.5, 1.).len(), 2);
+    }
+
+    #[test]
+    fn test_surface_geometry() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let (centroid, normal) = surface_geometry(&model, "Surface").unwrap();
+        assert!(normal.y < -0.99, "normal = {:?}", normal);
+        assert!(centroid.x.abs() < 1e-6, "centroid = {:?}", centroid);
+        assert!(centroid.y.abs() < 1e-6, "centroid = {:?}", centroid);
+        assert!((centroid.z - 1.5).abs() < 1e-6, "centroid = {:?}", centroid);
+
+        assert!(surface_geometry(&model, "Nope").is_err());
+
+        // The window is not centred on the part of the wall above a berm
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            berm_height: 0.8,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+        let (centroid, _) = surface_geometry(&model, "Surface").unwrap();
+        let exp = (4. * 2.2 * 1.9 - 1.5) / (4. * 2.2 - 1.);
+        assert!((centroid.z - exp).abs() < 1e-6, "centroid = {:?}", centroid);
+    }
+
+    #[test]
+    fn test_solar_aperture() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 2.,
+            window_height: 1.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let full = solar_aperture(&model, 180., 0.);
+        assert!((full - 2.).abs() < 1e-6, "aperture = {}", full);
+
+        let oblique = solar_aperture(&model, 180., 60.);
+        assert!((oblique - 1.).abs() < 1e-6, "aperture = {}", oblique);
+
+        assert!(solar_aperture(&model, 0., 30.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_loads_summary_luminaires() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 2.,
+            surface_height: 2.,
+            lighting_power: 120.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let summary = loads_summary(&model);
+        assert_eq!(summary.len(), 1);
+        let (name, power) = &summary[0];
+        assert_eq!(name, "the luminaire");
+        assert!((power - 120.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_total_internal_gains() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 2.,
+            surface_height: 2.,
+            heating_power: 1500.,
+            lighting_power: 120.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+        let mut model = SimpleModel::new("Two zones".to_string());
+        let mut header = simple_model::SimulationStateHeader::new();
+        crate::add_single_zone_to(&mut model, &mut header, &options, "A - ");
+        crate::add_single_zone_to(&mut model, &mut header, &options, "B - ");
+
+        // Heaters are not internal gains
+        let gains = total_internal_gains(&model);
+        assert!((gains - 240.).abs() < 1e-9, "gains = {}", gains);
+    }
+
+    #[test]
+    fn test_total_ua() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 2.,
+            surface_height: 2.,
+            infiltration_rate: 0.1,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let exp = 4. * 0.816 / 0.2 + 1.2 * 1005. * 0.1;
+        let ua = total_ua(&model);
+        assert!((ua - exp).abs() < 1e-3, "ua = {}... expecting {}", ua, exp);
+    }
+
+    #[test]
+    fn test_total_ua_ignores_porch() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            infiltration_rate: 0.1,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+        let (model, _header) = get_single_zone_test_building(&options);
+        let (with_porch, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            porch: Some((1.5, 2.4)),
+            ..options.clone()
+        });
+        assert!(with_porch.surfaces.len() > model.surfaces.len());
+
+        let (ua, found) = (total_ua(&model), total_ua(&with_porch));
+        assert!((ua - found).abs() < 1e-9, "{} vs {}", ua, found);
+        let (tau, found) = (
+            thermal_time_constant(&model),
+            thermal_time_constant(&with_porch),
+        );
+        assert!((tau - found).abs() < 1e-6, "{} vs {}", tau, found);
+    }
+
+    #[test]
+    fn test_total_ua_ignores_neighbor_obstruction() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            infiltration_rate: 0.1,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+        let (model, _header) = get_single_zone_test_building(&options);
+        let (with_neighbor, _header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                neighbor_obstruction: Some((10., 20., 9.)),
+                ..options.clone()
+            });
+        assert_eq!(with_neighbor.surfaces.len(), model.surfaces.len() + 1);
+
+        let (ua, found) = (total_ua(&model), total_ua(&with_neighbor));
+        assert!((ua - found).abs() < 1e-9, "{} vs {}", ua, found);
+        let (tau, found) = (
+            thermal_time_constant(&model),
+            thermal_time_constant(&with_neighbor),
+        );
+        assert!((tau - found).abs() < 1e-6, "{} vs {}", tau, found);
+    }
+
+    #[test]
+    fn test_construction_r_and_u_values() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 2.,
+            surface_height: 2.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let exp_r = 0.2 / 0.816;
+        let r = construction_r_value(&model, "the construction").unwrap();
+        assert!((r - exp_r).abs() < 1e-6, "r = {}... expecting {}", r, exp_r);
+
+        let u = construction_u_value(&model, "the construction", false).unwrap();
+        assert!((u - 1. / exp_r).abs() < 1e-6);
+
+        let u = construction_u_value(&model, "the construction", true).unwrap();
+        let exp_u = 1. / (exp_r + 0.13 + 0.04);
+        assert!((u - exp_u).abs() < 1e-6, "u = {}... expecting {}", u, exp_u);
+
+        assert!(construction_r_value(&model, "Nope").is_err());
+    }
+
+    #[test]
+    fn test_thermal_time_constant() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 2.,
+            surface_height: 2.,
+            construction: vec![TestMat::Polyurethane(0.02)],
+            ..Default::default()
+        };
+        let (light, _header) = get_single_zone_test_building(&options);
+        let (heavy, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            construction: vec![TestMat::Concrete(0.2), TestMat::Polyurethane(0.02)],
+            ..options
+        });
+
+        assert!(thermal_time_constant(&heavy) > thermal_time_constant(&light));
+    }
+
+    #[test]
+    fn test_validate_geometry() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 10.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+        let (mut model, _header) = get_single_zone_test_building(&options);
+        assert!(validate_geometry(&model).is_ok());
+
+        let (courtyard, _header) = get_courtyard_building(&options, 0.5);
+        assert!(validate_geometry(&courtyard).is_ok());
+
+        // Add a bow-tie
+        let mut the_loop = geometry3d::Loop3D::new();
+        for (x, z) in [(0., 0.), (2., 1.), (2., 0.), (0., 2.)].iter() {
+            the_loop.push(geometry3d::Point3D::new(*x, 5., *z)).unwrap();
+        }
+        the_loop.close().unwrap();
+        let construction = Rc::clone(&model.constructions[0]);
+        model.add_surface(Surface::new(
+            "bow-tie".to_string(),
+            Polygon3D::new(the_loop).unwrap(),
+            construction,
+        ));
+
+        // And a quadrilateral with one corner pulled off its plane
+        let mut the_loop = geometry3d::Loop3D::new();
+        for (x, y, z) in [(0., 5., 0.), (2., 5., 0.), (2., 5.5, 2.), (0., 5., 2.)].iter() {
+            the_loop.push(geometry3d::Point3D::new(*x, *y, *z)).unwrap();
+        }
+        the_loop.close().unwrap();
+        let construction = Rc::clone(&model.constructions[0]);
+        model.add_surface(Surface::new(
+            "warped".to_string(),
+            Polygon3D::new(the_loop).unwrap(),
+            construction,
+        ));
+
+        let problems = validate_geometry(&model).unwrap_err();
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].contains("bow-tie"), "{}", problems[0]);
+        assert!(
+            problems[1].contains("'warped' is not planar"),
+            "{}",
+            problems[1]
+        );
+    }
+
+    #[test]
+    fn test_describe_model() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 2.,
+            surface_height: 1.5,
+            lighting_power: 120.,
+            construction: vec![TestMat::Concrete(0.2), TestMat::Polyurethane(0.02)],
+            ..Default::default()
+        });
+
+        let summary = describe_model(&model);
+        assert!(summary.contains("Some space"), "{}", summary);
+        assert!(summary.contains("Surface (3.00 m2)"), "{}", summary);
+        assert!(summary.contains("[Material 0, Material 1]"), "{}", summary);
+        assert!(summary.contains("the luminaire (120.00 W)"), "{}", summary);
+        assert_eq!(summary.lines().count(), 7);
+    }
+
+    #[test]
+    fn test_models_equivalent() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 3.,
+            surface_height: 2.,
+            window_width: 1.,
+            window_height: 1.,
+            lighting_power: 100.,
+            construction: vec![TestMat::Concrete(0.2), TestMat::Polyurethane(0.02)],
+            ..Default::default()
+        };
+
+        let (a, _header) = get_single_zone_test_building(&options);
+        let (b, _header) = get_single_zone_test_building(&options);
+        assert!(models_equivalent(&a, &b).is_ok());
+
+        let (c, _header) = get_single_zone_test_building(&options.with_window_width(1.5));
+        let err = models_equivalent(&a, &c).unwrap_err();
+        assert!(err.contains("surface"), "{}", err);
+    }
+
+    #[test]
+    fn test_boundary_report() {
+        let (model, _header) = get_building_with_buffer(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 2.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let report = boundary_report(&model);
+        assert_eq!(report.len(), 3);
+        let partition = report.iter().find(|(n, _, _)| n == "Partition").unwrap();
+        assert_eq!(partition.1, "Space(Buffer space)");
+        assert_eq!(partition.2, "Space(Some space)");
+        let wall = report.iter().find(|(n, _, _)| n == "Surface").unwrap();
+        assert_eq!(wall.1, "Outdoor");
+        assert_eq!(wall.2, "Space(Some space)");
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let (width, height) = (10., 3.);
+        let (model, _header) = get_courtyard_building(
+            &SingleZoneTestBuildingOptions {
+                surface_width: width,
+                surface_height: height,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            },
+            0.5,
+        );
+        let (min, max) = bounding_box(&model);
+        assert!(
+            (max.x - min.x - width).abs() < 1e-6,
+            "{:?} - {:?}",
+            min,
+            max
+        );
+        assert!(
+            (max.y - min.y - width).abs() < 1e-6,
+            "{:?} - {:?}",
+            min,
+            max
+        );
+        assert!(
+            (max.z - min.z - height).abs() < 1e-6,
+            "{:?} - {:?}",
+            min,
+            max
+        );
+        assert!(min.z.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_shared_materials() {
+        let (mut model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            construction: vec![TestMat::Concrete(0.2), TestMat::Polyurethane(0.02)],
+            ..Default::default()
+        });
+        assert_eq!(
+            shared_materials(&model),
+            vec![("Material 0".to_string(), 1), ("Material 1".to_string(), 1)]
+        );
+
+        // A floor that reuses the concrete of the wall
+        let mut floor = Construction::new("Floor construction".to_string());
+        floor.materials.push(Rc::clone(&model.materials[0]));
+        model.add_construction(floor);
+        let shared = shared_materials(&model);
+        assert_eq!(shared[0], ("Material 0".to_string(), 2));
+        assert_eq!(shared[1], ("Material 1".to_string(), 1));
+    }
+
+    #[test]
+    fn test_daylight_geometry() {
+        let (model, _header) = get_deep_plan_room(
+            &SingleZoneTestBuildingOptions {
+                zone_volume: 3. * 12. * 3.,
+                surface_height: 3.,
+                window_width: 2.,
+                window_height: 1.5,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            },
+            12.,
+        );
+
+        let geometry = daylight_geometry(&model);
+        assert_eq!(geometry.len(), 1);
+        let g = &geometry[0];
+        assert_eq!(g.name, "window one");
+        assert!((g.window_head_height - 2.25).abs() < 1e-6, "{:?}", g);
+        assert!((g.room_depth - 12.).abs() < 1e-6, "{:?}", g);
+        assert!((g.glazed_area - 3.).abs() < 1e-6, "{:?}", g);
+    }
+
+    #[test]
+    fn test_main_wall() {
+        let (mut model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            floor_plenum: true,
+            cathedral_ceiling: true,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+        // Move the wall away from the front
+        model.surfaces.rotate_left(1);
+        assert_ne!(model.surfaces[0].name, "Surface");
+
+        let wall = main_wall(&model).unwrap();
+        assert_eq!(wall.name, "Surface");
+        assert!(wall.vertices.normal().y < -0.99);
+        assert!((wall.area() - (12. - 1.)).abs() < 1e-6);
+
+        let (model, _header) = get_sunspace_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+        assert!(main_wall(&model).is_err());
+    }
+
+    #[test]
+    fn test_shgc_area_by_orientation() {
+        let (mut model, mut header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_width: 4.,
+                surface_height: 3.,
+                window_width: 2.,
+                window_height: 1.,
+                glazing: Some(crate::Glazing::Single),
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        // A tinted window, facing North
+        let mut tinted = Normal::new("tinted glass".to_string());
+        tinted.set_solar_transmittance(0.4);
+        let tinted = model.add_substance(tinted.wrap());
+        let tinted = model.add_material(Material::new("tinted".to_string(), tinted, 0.006));
+        let mut construction = Construction::new("tinted window".to_string());
+        construction.materials.push(tinted);
+        let construction = model.add_construction(construction);
+        let the_loop = crate::geometry::vertical_rectangle(0., (1., 5.), (-1., 5.), 1., 2.5);
+        let window = Fenestration::new(
+            "North window".to_string(),
+            Polygon3D::new(the_loop).unwrap(),
+            construction,
+            FenestrationPositions::Binary,
+            FenestrationType::Window,
+        );
+        model.add_fenestration(window, &mut header);
+
+        let found = shgc_area_by_orientation(&model);
+        assert_eq!(found.len(), 2, "{:?}", found);
+        let (north, south) = (found[0], found[1]);
+        assert!(
+            north.0.abs() < 1e-6 && (north.1 - 3. * 0.4).abs() < 1e-6,
+            "{:?}",
+            north
+        );
+        assert!(
+            (south.0 - 180.).abs() < 1e-6
+                && (south.1 - 2. * crate::GLAZING_PANE_TRANSMITTANCE).abs() < 1e-6,
+            "{:?}",
+            south
+        );
+    }
+
+    #[test]
+    fn test_rc_report() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let report = rc_report(&model);
+        let count = |name: &str| report.iter().find(|(n, _)| n == name).unwrap().1;
+        // Shared by the wall and the window (and held by the model)
+        assert!(count("the construction") >= 3);
+        assert_eq!(count("Surface"), 1);
+        assert_eq!(count("window one"), 1);
+        assert_eq!(
+            report.len(),
+            model.spaces.len()
+                + model.substances.len()
+                + model.materials.len()
+                + model.constructions.len()
+                + model.surfaces.len()
+                + model.fenestrations.len()
+        );
+    }
+
+    #[test]
+    fn test_analytical_steady_state_temperature() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 2.,
+            surface_height: 2.,
+            infiltration_rate: 0.01,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        // 4 m2 of 20cm of concrete (k = 0.816) plus the infiltration
+        let ua = 4. * 0.816 / 0.2 + 1.2 * 1005. * 0.01;
+        let exp = 10. + 1000. / ua;
+        let found = analytical_steady_state_temperature(&model, 10., 1000.);
+        assert!(
+            (found - exp).abs() < 1e-4,
+            "found {}... expecting {}",
+            found,
+            exp
+        );
+    }
+
+    #[test]
+    fn test_net_opaque_area() {
+        let (mut model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+        assert!((net_opaque_area(&model, "Surface").unwrap() - 12.).abs() < 1e-6);
+        assert!(net_opaque_area(&model, "Nope").is_err());
+
+        // A wall with a door and a window
+        let mut wall = Polygon3D::new(crate::geometry::vertical_rectangle(
+            0.,
+            (-2., 5.),
+            (2., 5.),
+            0.,
+            3.,
+        ))
+        .unwrap();
+        let door = crate::geometry::vertical_rectangle(0., (-1.5, 5.), (-0.5, 5.), 0.1, 2.1);
+        let window = crate::geometry::vertical_rectangle(0., (0.2, 5.), (1.7, 5.), 1., 2.);
+        wall.cut_hole(door).unwrap();
+        wall.cut_hole(window).unwrap();
+        let construction = Rc::clone(&model.constructions[0]);
+        model.add_surface(Surface::new("Back wall".to_string(), wall, construction));
+
+        let found = net_opaque_area(&model, "Back wall").unwrap();
+        assert!((found - (12. - 2. - 1.5)).abs() < 1e-6, "found {}", found);
+    }
+
+    #[test]
+    fn test_wall_area_by_orientation() {
+        let (model, _header) = get_core_perimeter_floor(&SingleZoneTestBuildingOptions {
+            zone_volume: 30. * 20. * 3.,
+            surface_width: 30.,
+            surface_height: 3.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let found = wall_area_by_orientation(&model);
+        let exp = [(0., 90.), (90., 60.), (180., 90.), (270., 60.)];
+        assert_eq!(found.len(), exp.len(), "{:?}", found);
+        for ((a, area), (exp_a, exp_area)) in found.iter().zip(exp.iter()) {
+            assert!((a - exp_a).abs() < 1e-6, "{:?}", found);
+            assert!((area - exp_area).abs() < 1e-6, "{:?}", found);
+        }
+    }
+
+    #[test]
+    fn test_validate_openings() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+        let (mut model, mut header) = get_single_zone_test_building(&options);
+        assert!(validate_openings(&model).is_ok());
+        let (courtyard, _header) = get_courtyard_building(&options, 0.5);
+        assert!(validate_openings(&courtyard).is_ok());
+
+        // The wall goes from x = -2 to x = 2, and from z = 0 to z = 3
+        let openings = [
+            ("Too large", (-2.5, 0.), (2.5, 0.), 0.5),
+            ("Off centre", (1.5, 0.), (2.5, 0.), 1.5),
+            ("Floating", (-0.5, 5.), (0.5, 5.), 1.),
+        ];
+        for (name, start, end, z) in openings {
+            let the_loop = crate::geometry::vertical_rectangle(0., start, end, z, z + 1.);
+            let window = Fenestration::new(
+                name.to_string(),
+                Polygon3D::new(the_loop).unwrap(),
+                Rc::clone(&model.constructions[0]),
+                FenestrationPositions::Binary,
+                FenestrationType::Window,
+            );
+            model.add_fenestration(window, &mut header);
+        }
+
+        let problems = validate_openings(&model).unwrap_err();
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert!(
+            problems[0].contains("'Too large' extends"),
+            "{}",
+            problems[0]
+        );
+        assert!(
+            problems[1].contains("'Off centre' extends"),
+            "{}",
+            problems[1]
+        );
+        assert!(
+            problems[2].contains("'Floating' does not"),
+            "{}",
+            problems[2]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no heat losses")]
+    fn test_thermal_time_constant_no_losses() {
+        let (model, _header) = crate::get_adiabatic_box(&SingleZoneTestBuildingOptions {
+            zone_volume: 4. * 5. * 3.,
+            surface_width: 4.,
+            surface_height: 3.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+        thermal_time_constant(&model);
+    }
+}
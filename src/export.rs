@@ -0,0 +1,218 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Exports the models to files, for visual debugging and reviews
+
+use crate::analysis::material_resistance;
+use crate::geometry::{loop_points, polygon_loops, triangulate};
+use geometry3d::Polygon3D;
+use simple_model::{substance::Substance, SimpleModel};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Writes one polygon as a Wavefront OBJ object, given the number of
+/// vertices already written to the file
+fn write_polygon<W: Write>(
+    out: &mut W,
+    name: &str,
+    polygon: &Polygon3D,
+    n_written: &mut usize,
+) -> std::io::Result<()> {
+    writeln!(out, "o {}", name)?;
+    let loops = polygon_loops(polygon);
+    let n_vertices: usize = loops.iter().map(|l| l.n_vertices()).sum();
+    for l in loops {
+        for p in loop_points(l) {
+            writeln!(out, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+    }
+    for [a, b, c] in triangulate(polygon) {
+        let offset = *n_written + 1; // OBJ indices start at 1
+        writeln!(out, "f {} {} {}", a + offset, b + offset, c + offset)?;
+    }
+    *n_written += n_vertices;
+    Ok(())
+}
+
+/// Writes the geometry of every surface and fenestration of the model into a
+/// [Wavefront OBJ](https://en.wikipedia.org/wiki/Wavefront_.obj_file) file at
+/// `path`, so it can be inspected in a 3D viewer.
+///
+/// Each element becomes an object (named after it) whose polygon is
+/// triangulated, honouring its holes, with the triangles facing the same
+/// way as the element. The model is not modified.
+pub fn write_obj(model: &SimpleModel, path: &Path) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    let mut n_written = 0;
+    for s in model.surfaces.iter() {
+        write_polygon(&mut out, &s.name, &s.vertices, &mut n_written)?;
+    }
+    for f in model.fenestrations.iter() {
+        write_polygon(&mut out, &f.name, &f.vertices, &mut n_written)?;
+    }
+    out.flush()
+}
+
+/// Quotes a CSV field if it needs it
+fn csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Writes a CSV table with one row per layer of every construction in the model,
+/// with the columns `construction`, `layer` (from the outside in, starting at 0),
+/// `material`, `thickness` (m), `conductivity` (W/mK) and `resistance` (m2K/W).
+///
+/// Gas layers have no conductivity (the cell is empty), and their resistance
+/// is the one assumed by [`total_ua`](crate::total_ua). The model is not modified.
+pub fn write_constructions_csv(model: &SimpleModel, path: &Path) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(
+        out,
+        "construction,layer,material,thickness,conductivity,resistance"
+    )?;
+    for construction in model.constructions.iter() {
+        for (i, material) in construction.materials.iter().enumerate() {
+            let conductivity = match &*material.substance {
+                Substance::Normal(s) => s
+                    .thermal_conductivity()
+                    .map(|k| k.to_string())
+                    .unwrap_or_default(),
+                _ => String::new(),
+            };
+            writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                csv_field(&construction.name),
+                i,
+                csv_field(&material.name),
+                material.thickness,
+                conductivity,
+                material_resistance(material)
+            )?;
+        }
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use crate::{
+        get_courtyard_building, get_single_zone_test_building, SingleZoneTestBuildingOptions,
+        TestMat,
+    };
+
+    /// A path in the temporary directory that is unique to this process,
+    /// so that concurrent test runs do not overwrite each other's files
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "simple_test_models_{}_{}",
+            std::process::id(),
+            name
+        ))
+    }
+
+    /// Counts the vertices and faces in an OBJ file
+    fn count(path: &Path) -> (usize, usize) {
+        let content = std::fs::read_to_string(path).unwrap();
+        let n_v = content.lines().filter(|l| l.starts_with("v ")).count();
+        let n_f = content.lines().filter(|l| l.starts_with("f ")).count();
+        (n_v, n_f)
+    }
+
+    #[test]
+    fn test_write_obj() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+        let (model, _header) = get_single_zone_test_building(&options);
+        let path = temp_path("wall.obj");
+        write_obj(&model, &path).unwrap();
+
+        // The wall has 8 vertices and is split into 8 triangles; the window
+        // has 4 vertices and 2 triangles
+        assert_eq!(count(&path), (12, 10));
+        std::fs::remove_file(&path).unwrap();
+
+        // The triangles cover the wall, but not the window
+        let wall = &model.surfaces[0].vertices;
+        let points: Vec<_> = polygon_loops(wall)
+            .iter()
+            .flat_map(|l| loop_points(l))
+            .collect();
+        let area: crate::Float = triangulate(wall)
+            .iter()
+            .map(|[a, b, c]| {
+                let (u, v) = (points[*b] - points[*a], points[*c] - points[*a]);
+                let n = geometry3d::Vector3D::new(
+                    u.y * v.z - u.z * v.y,
+                    u.z * v.x - u.x * v.z,
+                    u.x * v.y - u.y * v.x,
+                );
+                assert!(n * wall.normal() > 0., "Triangle facing the wrong way");
+                n.length() / 2.
+            })
+            .sum();
+        assert!((area - 11.).abs() < 1e-6, "area = {}", area);
+
+        // A floor with a courtyard
+        let (model, _header) = get_courtyard_building(&options, 0.4);
+        let path = temp_path("courtyard.obj");
+        write_obj(&model, &path).unwrap();
+        assert_eq!(count(&path), (8 * 4 + 8, 8 * 2 + 8));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_constructions_csv() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            construction: vec![TestMat::Concrete(0.2), TestMat::Polyurethane(0.05)],
+            ..Default::default()
+        });
+        let path = temp_path("constructions.csv");
+        write_constructions_csv(&model, &path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let rows: Vec<Vec<&str>> = content.lines().map(|l| l.split(',').collect()).collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|r| r.len() == 6));
+        assert_eq!(rows[1][0], "the construction");
+        assert_eq!(rows[2][1], "1");
+        assert_eq!(rows[2][2], "Material 1");
+        let r: crate::Float = rows[2][5].parse().unwrap();
+        assert!((r - 0.05 / 0.0252).abs() < 1e-6);
+    }
+}
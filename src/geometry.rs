@@ -0,0 +1,431 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Small geometric helpers shared by the test models.
+//!
+//! All the models are described in "local" coordinates: the `x` axis
+//! runs along the main (i.e., South) wall, the `y` axis points into the
+//! building and `z` points up. These local coordinates are then rotated
+//! around the `z` axis by the `orientation` option.
+
+use crate::Float;
+use geometry3d::{Loop3D, Point3D, Polygon3D, Vector3D};
+
+/// Transforms a point in local coordinates into a global [`Point3D`],
+/// rotating it by `orientation` degrees around the `z` axis.
+pub(crate) fn local_point(orientation: Float, x: Float, y: Float, z: Float) -> Point3D {
+    let angle = orientation.to_radians();
+    Point3D::new(
+        x * angle.cos() - y * angle.sin(),
+        x * angle.sin() + y * angle.cos(),
+        z,
+    )
+}
+
+/// Builds a closed [`Loop3D`] out of a list of points in local coordinates.
+///
+/// The normal of the loop follows the right-hand rule.
+pub(crate) fn local_loop(orientation: Float, points: &[(Float, Float, Float)]) -> Loop3D {
+    let mut the_loop = Loop3D::new();
+    for (x, y, z) in points {
+        the_loop.push(local_point(orientation, *x, *y, *z)).unwrap();
+    }
+    the_loop.close().unwrap();
+    the_loop
+}
+
+/// Builds a vertical rectangle going from `start` to `end` (in plan,
+/// local coordinates), between `z_bottom` and `z_top`.
+///
+/// The normal of the resulting loop points to the right of the
+/// `start -> end` direction (when looking from above). So, going
+/// counterclockwise around a footprint produces outward-facing walls.
+pub(crate) fn vertical_rectangle(
+    orientation: Float,
+    start: (Float, Float),
+    end: (Float, Float),
+    z_bottom: Float,
+    z_top: Float,
+) -> Loop3D {
+    let (x0, y0) = start;
+    let (x1, y1) = end;
+    local_loop(
+        orientation,
+        &[
+            (x0, y0, z_bottom),
+            (x1, y1, z_bottom),
+            (x1, y1, z_top),
+            (x0, y0, z_top),
+        ],
+    )
+}
+
+/// Builds a horizontal rectangle at height `z`, spanning from
+/// `(x0, y0)` to `(x1, y1)` in plan (local coordinates), with
+/// `x0 < x1` and `y0 < y1`.
+///
+/// If `facing_up` the normal points up; otherwise, it points down.
+pub(crate) fn horizontal_rectangle(
+    orientation: Float,
+    (x0, y0): (Float, Float),
+    (x1, y1): (Float, Float),
+    z: Float,
+    facing_up: bool,
+) -> Loop3D {
+    if facing_up {
+        local_loop(
+            orientation,
+            &[(x0, y0, z), (x1, y0, z), (x1, y1, z), (x0, y1, z)],
+        )
+    } else {
+        local_loop(
+            orientation,
+            &[(x0, y0, z), (x0, y1, z), (x1, y1, z), (x1, y0, z)],
+        )
+    }
+}
+
+/// Mirrors a [`Loop3D`] across the `x = 0` plane (i.e., negates `x`) and
+/// reverses the order of its vertices, so that its normal is also mirrored
+/// (instead of flipped).
+fn mirror_loop(the_loop: &Loop3D) -> Loop3D {
+    let mut mirrored = Loop3D::new();
+    for p in loop_points(the_loop).into_iter().rev() {
+        mirrored.push(Point3D::new(-p.x, p.y, p.z)).unwrap();
+    }
+    mirrored.close().unwrap();
+    mirrored
+}
+
+/// Mirrors a [`Polygon3D`] (including its holes) across the `x = 0` plane,
+/// keeping its normal pointing to the same (mirrored) side. See `flip_handedness`
+pub(crate) fn mirror_polygon(polygon: &Polygon3D) -> Polygon3D {
+    let mut mirrored = Polygon3D::new(mirror_loop(polygon.outer())).unwrap();
+    for hole in polygon.inner() {
+        mirrored.cut_hole(mirror_loop(hole)).unwrap();
+    }
+    mirrored
+}
+
+/// Builds the two slopes of a gable (i.e., pitched) roof over the footprint of the
+/// zone (`half_width` to each side of `x = 0` and `depth` deep, in local coordinates),
+/// rising from `z` to a ridge `rise` higher that runs along `x`, halfway through the depth.
+///
+/// Returns the South and the North slopes, whose normals point up (and outwards).
+pub(crate) fn gable_slopes(
+    orientation: Float,
+    half_width: Float,
+    depth: Float,
+    z: Float,
+    rise: Float,
+) -> (Loop3D, Loop3D) {
+    let (l, ridge) = (half_width, depth / 2.);
+    let south = local_loop(
+        orientation,
+        &[
+            (-l, 0., z),
+            (l, 0., z),
+            (l, ridge, z + rise),
+            (-l, ridge, z + rise),
+        ],
+    );
+    let north = local_loop(
+        orientation,
+        &[
+            (-l, ridge, z + rise),
+            (l, ridge, z + rise),
+            (l, depth, z),
+            (-l, depth, z),
+        ],
+    );
+    (south, north)
+}
+
+/// Calculates the azimuth of a [`Vector3D`], in degrees.
+///
+/// This is measured clockwise from North (i.e., `y`), so that
+/// East is `90`, South is `180` and West is `270`. The result is
+/// always within `[0, 360)`.
+pub(crate) fn azimuth(v: Vector3D) -> Float {
+    let a = v.x.atan2(v.y).to_degrees();
+    if a < 0. {
+        a + 360.
+    } else {
+        a
+    }
+}
+
+/// Calculates the absolute difference between two angles (in degrees),
+/// accounting for the wrap-around at `360`
+pub(crate) fn angle_difference(a: Float, b: Float) -> Float {
+    let d = (a - b).rem_euclid(360.);
+    d.min(360. - d)
+}
+
+/// Collects the vertices of a [`Loop3D`]
+pub(crate) fn loop_points(the_loop: &Loop3D) -> Vec<Point3D> {
+    (0..the_loop.n_vertices()).map(|i| the_loop[i]).collect()
+}
+
+/// Collects all the loops of a [`Polygon3D`]: the outer one first,
+/// followed by the holes.
+pub(crate) fn polygon_loops(polygon: &Polygon3D) -> Vec<&Loop3D> {
+    let mut loops = vec![polygon.outer()];
+    loops.extend(polygon.inner().iter());
+    loops
+}
+
+/// Calculates the centroid (i.e., centre of area) of a planar [`Polygon3D`],
+/// accounting for its holes
+pub(crate) fn polygon_centroid(polygon: &Polygon3D) -> Point3D {
+    let normal = polygon.normal();
+    let (mut area, mut mx, mut my) = (0.0, 0.0, 0.0);
+    for (i, the_loop) in polygon_loops(polygon).into_iter().enumerate() {
+        let flat = project(&loop_points(the_loop), normal);
+        let n = flat.len();
+        let (mut a, mut x, mut y) = (0.0, 0.0, 0.0);
+        for j in 0..n {
+            let (x0, y0) = flat[j];
+            let (x1, y1) = flat[(j + 1) % n];
+            let c = x0 * y1 - x1 * y0;
+            a += c / 2.;
+            x += (x0 + x1) * c / 6.;
+            y += (y0 + y1) * c / 6.;
+        }
+        // Whatever their winding, the outer loop adds and holes subtract
+        let sign = if (a < 0.) == (i == 0) { -1. } else { 1. };
+        area += sign * a;
+        mx += sign * x;
+        my += sign * y;
+    }
+    let (u, v) = (mx / area, my / area);
+
+    // Recover the dropped coordinate from the plane of the polygon
+    let p0 = polygon.outer()[0];
+    let (nx, ny, nz) = (normal.x.abs(), normal.y.abs(), normal.z.abs());
+    if nz >= nx && nz >= ny {
+        let z = p0.z - (normal.x * (u - p0.x) + normal.y * (v - p0.y)) / normal.z;
+        Point3D::new(u, v, z)
+    } else if ny >= nx {
+        let y = p0.y - (normal.x * (u - p0.x) + normal.z * (v - p0.z)) / normal.y;
+        Point3D::new(u, y, v)
+    } else {
+        let x = p0.x - (normal.y * (u - p0.y) + normal.z * (v - p0.z)) / normal.x;
+        Point3D::new(x, u, v)
+    }
+}
+
+/// Projects points onto the plane of the two axes that are most
+/// perpendicular to `normal`, so 2D algorithms can be
+/// used on planar 3D shapes
+pub(crate) fn project(points: &[Point3D], normal: Vector3D) -> Vec<(Float, Float)> {
+    let (nx, ny, nz) = (normal.x.abs(), normal.y.abs(), normal.z.abs());
+    points
+        .iter()
+        .map(|p| {
+            if nz >= nx && nz >= ny {
+                (p.x, p.y)
+            } else if ny >= nx {
+                (p.x, p.z)
+            } else {
+                (p.y, p.z)
+            }
+        })
+        .collect()
+}
+
+/// Checks whether segments `a0-a1` and `b0-b1` cross each other (touching
+/// at an end point does not count)
+pub(crate) fn segments_cross(
+    a0: (Float, Float),
+    a1: (Float, Float),
+    b0: (Float, Float),
+    b1: (Float, Float),
+) -> bool {
+    let orient = |p: (Float, Float), q: (Float, Float), r: (Float, Float)| {
+        (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)
+    };
+    let tiny = 1e-9;
+    let d1 = orient(b0, b1, a0);
+    let d2 = orient(b0, b1, a1);
+    let d3 = orient(a0, a1, b0);
+    let d4 = orient(a0, a1, b1);
+    ((d1 > tiny && d2 < -tiny) || (d1 < -tiny && d2 > tiny))
+        && ((d3 > tiny && d4 < -tiny) || (d3 < -tiny && d4 > tiny))
+}
+
+/// Checks whether point `p` is inside (or on the edge of) the planar polygon `ring`
+pub(crate) fn point_in_ring(p: (Float, Float), ring: &[(Float, Float)]) -> bool {
+    let tiny = 1e-6;
+    let n = ring.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (ring[i], ring[(i + 1) % n]);
+        // On the edge
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let len2 = dx * dx + dy * dy;
+        let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0., 1.);
+        let (ex, ey) = (a.0 + t * dx - p.0, a.1 + t * dy - p.1);
+        if ex * ex + ey * ey < tiny * tiny {
+            return true;
+        }
+        // Ray casting, towards +x
+        if (a.1 > p.1) != (b.1 > p.1) && p.0 < a.0 + (p.1 - a.1) * dx / dy {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Twice the signed area of the triangle `a-b-c` (positive when counterclockwise)
+fn orient(a: (Float, Float), b: (Float, Float), c: (Float, Float)) -> Float {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+/// Twice the signed area of a 2D loop given by indices into `pts`
+fn signed_area(pts: &[(Float, Float)], ring: &[usize]) -> Float {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (pts[ring[i]], pts[ring[(i + 1) % n]]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum()
+}
+
+/// Triangulates a planar [`Polygon3D`], honouring its holes.
+///
+/// The triangles index the vertices of [`polygon_loops`] (i.e., the outer
+/// loop first, followed by the holes) as if they were a single list, and they
+/// are wound so that their normals match the normal of the polygon. Holes are
+/// bridged into the outer loop (i.e., turned into "keyholes"), which is then
+/// triangulated by ear clipping.
+pub(crate) fn triangulate(polygon: &Polygon3D) -> Vec<[usize; 3]> {
+    let normal = polygon.normal();
+    let loops = polygon_loops(polygon);
+    let points: Vec<Point3D> = loops.iter().flat_map(|l| loop_points(l)).collect();
+    let pts = project(&points, normal);
+
+    // Index the loops, with the outer one counterclockwise and holes clockwise
+    let mut rings: Vec<Vec<usize>> = Vec::with_capacity(loops.len());
+    let mut start = 0;
+    for (i, l) in loops.iter().enumerate() {
+        let mut ring: Vec<usize> = (start..start + l.n_vertices()).collect();
+        if (signed_area(&pts, &ring) > 0.) != (i == 0) {
+            ring.reverse();
+        }
+        start += l.n_vertices();
+        rings.push(ring);
+    }
+    let mut ring = rings.remove(0);
+
+    // Bridge the holes, rightmost first
+    let rightmost = |h: &Vec<usize>| {
+        (0..h.len())
+            .max_by(|a, b| pts[h[*a]].0.partial_cmp(&pts[h[*b]].0).unwrap())
+            .unwrap()
+    };
+    rings.sort_by(|a, b| {
+        let (xa, xb) = (pts[a[rightmost(a)]].0, pts[b[rightmost(b)]].0);
+        xb.partial_cmp(&xa).unwrap()
+    });
+    for (h, hole) in rings.iter().enumerate() {
+        let mi = rightmost(hole);
+        let m = pts[hole[mi]];
+        let edges_of = |r: &Vec<usize>| {
+            let n = r.len();
+            (0..n)
+                .map(|i| (pts[r[i]], pts[r[(i + 1) % n]]))
+                .collect::<Vec<_>>()
+        };
+        let mut edges = edges_of(&ring);
+        for other in rings.iter().skip(h) {
+            edges.extend(edges_of(other));
+        }
+        let dist = |p: (Float, Float)| (p.0 - m.0).powi(2) + (p.1 - m.1).powi(2);
+        let pi = (0..ring.len())
+            .filter(|i| {
+                let p = pts[ring[*i]];
+                !edges.iter().any(|(a, b)| segments_cross(m, p, *a, *b))
+            })
+            .min_by(|a, b| {
+                dist(pts[ring[*a]])
+                    .partial_cmp(&dist(pts[ring[*b]]))
+                    .unwrap()
+            })
+            .expect("Could not bridge a hole of the polygon");
+
+        let mut bridged = Vec::with_capacity(ring.len() + hole.len() + 2);
+        bridged.extend_from_slice(&ring[..=pi]);
+        bridged.extend_from_slice(&hole[mi..]);
+        bridged.extend_from_slice(&hole[..=mi]);
+        bridged.extend_from_slice(&ring[pi..]);
+        ring = bridged;
+    }
+
+    // Clip the ears
+    let tiny = 1e-9;
+    let same =
+        |a: (Float, Float), b: (Float, Float)| (a.0 - b.0).abs() < tiny && (a.1 - b.1).abs() < tiny;
+    let mut triangles = Vec::with_capacity(ring.len());
+    while ring.len() > 3 {
+        let n = ring.len();
+        let is_ear = |i: usize| {
+            let (a, b, c) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+            let (pa, pb, pc) = (pts[a], pts[b], pts[c]);
+            if orient(pa, pb, pc) <= tiny {
+                return false;
+            }
+            !ring.iter().any(|j| {
+                let p = pts[*j];
+                !same(p, pa)
+                    && !same(p, pb)
+                    && !same(p, pc)
+                    && orient(pa, pb, p) >= -tiny
+                    && orient(pb, pc, p) >= -tiny
+                    && orient(pc, pa, p) >= -tiny
+            })
+        };
+        // Degenerate loops may have no proper ears left
+        let i = (0..n).find(|i| is_ear(*i)).unwrap_or(0);
+        triangles.push([ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]]);
+        ring.remove(i);
+    }
+    triangles.push([ring[0], ring[1], ring[2]]);
+
+    // Drop the slivers left by the bridges, and match the normal
+    triangles
+        .into_iter()
+        .filter(|[a, b, c]| orient(pts[*a], pts[*b], pts[*c]).abs() > tiny)
+        .map(|[a, b, c]| {
+            let (u, v) = (points[b] - points[a], points[c] - points[a]);
+            let n = Vector3D::new(
+                u.y * v.z - u.z * v.y,
+                u.z * v.x - u.x * v.z,
+                u.x * v.y - u.y * v.x,
+            );
+            if n * normal < 0. {
+                [a, c, b]
+            } else {
+                [a, b, c]
+            }
+        })
+        .collect()
+}
@@ -25,23 +25,51 @@ SOFTWARE.
 /// The kind of Floating point number used in the
 /// library... the `"float"` feature means it becomes `f32`
 /// and `f64` is used otherwise.
+///
+/// It is public so that downstream crates can match the
+/// precision with which the models are built.
 #[cfg(feature = "float")]
-type Float = f32;
+pub type Float = f32;
 
+/// The kind of Floating point number used in the
+/// library... the `"float"` feature means it becomes `f32`
+/// and `f64` is used otherwise.
+///
+/// It is public so that downstream crates can match the
+/// precision with which the models are built.
 #[cfg(not(feature = "float"))]
-type Float = f64;
+pub type Float = f64;
 
-use geometry3d::{Loop3D, Point3D, Polygon3D};
+use geometry3d::{Loop3D, Polygon3D};
+use std::collections::HashMap;
 use std::rc::Rc;
 
+mod geometry;
+use geometry::{gable_slopes, horizontal_rectangle, mirror_polygon, vertical_rectangle};
+
+mod presets;
+pub use presets::*;
+use presets::{add_floor_plenum, zone_depth};
+
+mod analysis;
+pub use analysis::*;
+
+mod modify;
+pub use modify::*;
+
+mod export;
+pub use export::*;
+
 use simple_model::{
     hvac::ElectricHeater,
+    substance::Substance,
     substance::{gas::StandardGas, Gas, Normal as NormalSubstance},
     Boundary, Construction, Fenestration, FenestrationPositions, FenestrationType, Infiltration,
     Luminaire, Material, SimpleModel, SimulationStateHeader, Space, Surface,
 };
 
 /// The test material
+#[derive(Clone)]
 pub enum TestMat {
     /// A Concrete with a certain `Float` thickness   
     ///
@@ -72,9 +100,138 @@ pub enum TestMat {
 
     /// Air Cavity
     Air(Float),
+
+    /// A purely resistive layer with a certain `Float` thermal
+    /// resistance (i.e., R-value), in m2K/W
+    ///
+    /// It is modelled as a layer of [`RESISTANCE_LAYER_THICKNESS`]
+    /// whose conductivity is calculated to produce the required resistance.
+    ///
+    /// # Properties
+    /// * density: 1.
+    /// * Specific heat: 1000.
+    /// * Thermal Cond.: `RESISTANCE_LAYER_THICKNESS / R`
+    /// * Emmisivity: From `options.emmisivity
+    Resistance(Float),
+}
+
+/// The thickness of the layers built from [`TestMat::Resistance`], in m
+pub const RESISTANCE_LAYER_THICKNESS: Float = 0.01;
+
+/// The thickness of each glass pane of a [`Glazing`], in m
+pub const GLAZING_PANE_THICKNESS: Float = 0.006;
+
+/// The solar transmittance of each glass pane of a [`Glazing`]
+pub const GLAZING_PANE_TRANSMITTANCE: Float = 0.8;
+
+/// The thickness of the air gaps between the panes of a [`Glazing`], in m
+pub const GLAZING_GAP_THICKNESS: Float = 0.012;
+
+/// A shorthand for common window constructions, by number of panes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Glazing {
+    /// One glass pane
+    Single,
+
+    /// Two glass panes separated by an air gap
+    Double,
+
+    /// Three glass panes separated by two air gaps
+    Triple,
+}
+
+impl Glazing {
+    /// The layers of the window construction, from the outside in.
+    ///
+    /// Panes are [`TestMat::Glass`] of [`GLAZING_PANE_THICKNESS`] and
+    /// [`GLAZING_PANE_TRANSMITTANCE`]; gaps are [`TestMat::Air`] of
+    /// [`GLAZING_GAP_THICKNESS`].
+    pub fn layers(&self) -> Vec<TestMat> {
+        let n_panes = match self {
+            Glazing::Single => 1,
+            Glazing::Double => 2,
+            Glazing::Triple => 3,
+        };
+        let mut layers = Vec::with_capacity(2 * n_panes - 1);
+        for i in 0..n_panes {
+            if i > 0 {
+                layers.push(TestMat::Air(GLAZING_GAP_THICKNESS));
+            }
+            layers.push(TestMat::Glass(
+                GLAZING_PANE_THICKNESS,
+                GLAZING_PANE_TRANSMITTANCE,
+            ));
+        }
+        layers
+    }
+}
+
+/// A layer of a construction as it is usually tabulated (e.g., in the `Material`
+/// objects of EnergyPlus), from the outside in. All values are in SI units.
+#[derive(Clone, Debug)]
+pub struct LayerSpec {
+    /// The name of the layer, given to its substance and its material
+    pub name: String,
+
+    /// The thickness, in m
+    pub thickness: Float,
+
+    /// The thermal conductivity, in W/mK
+    pub conductivity: Float,
+
+    /// The density, in kg/m3
+    pub density: Float,
+
+    /// The specific heat capacity, in J/kgK
+    pub specific_heat: Float,
+}
+
+/// The system of units in which [`SingleZoneTestBuildingOptions`] are given
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Units {
+    /// The International System (m, W, J), which is what `simple_model` uses
+    SI,
+
+    /// Inch-pound units. See [`SingleZoneTestBuildingOptions::in_si`] for the
+    /// unit of each field.
+    IP,
+}
+
+/// How the floor of the zone of [`get_single_zone_test_building`] is coupled to
+/// the ground. See `floor_type` in [`SingleZoneTestBuildingOptions`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloorType {
+    /// A slab lying on the ground, whose exterior faces [`Boundary::Ground`]
+    SlabOnGrade,
+
+    /// A floor suspended over a vented crawlspace, which is assumed to be at the
+    /// outdoor temperature, so its exterior faces the outdoors
+    SuspendedVented,
 }
 
+/// Metres per foot
+const M_PER_FT: Float = 0.3048;
+
+/// Metres per inch
+const M_PER_IN: Float = 0.0254;
+
+/// W per Btu/h
+const W_PER_BTU_H: Float = 0.293_071_07;
+
+/// m2K/W per h ft2 F/Btu
+const SI_PER_IP_RESISTANCE: Float = 0.176_110_2;
+
+/// J/m2K per Btu/ft2F
+const SI_PER_IP_AREAL_CAPACITANCE: Float = 20_441.7;
+
+/// m3/s per ft3/min
+const SI_PER_IP_FLOW: Float = 0.000_471_947_4;
+
+/// J/K per Btu/F
+const SI_PER_IP_CAPACITANCE: Float = 1_899.1;
+
 /// Characteristics of the Zone of the single-zone model
+#[derive(Clone)]
 pub struct SingleZoneTestBuildingOptions {
     /// Volume, in m3
     pub zone_volume: Float,
@@ -82,19 +239,127 @@ pub struct SingleZoneTestBuildingOptions {
     /// The construction, built out of [`TestMat`]
     pub construction: Vec<TestMat>, // Explicitly mentioned
 
+    /// Builds the `construction` with its layers in reverse order (i.e., swapping
+    /// the inside and the outside), for checking that the solver treats the
+    /// order of layers correctly.
+    pub reverse_layers: bool,
+
+    /// The number of equal sublayers (i.e., materials) in which each
+    /// [`TestMat`] of `construction` and `window_construction` is split, for
+    /// discretization studies. Defaults to `1` (i.e., no splitting).
+    ///
+    /// The sublayers use the same substance and add up to the original layer, so
+    /// the physics do not change. [`TestMat::Resistance`] layers are split into
+    /// `n` layers of `R/n`, and [`TestMat::Air`] cavities are never split.
+    pub split_layers: usize,
+
+    /// The overall U-value of the walls, in W/m2K (no air films).
+    ///
+    /// When set together with `wall_areal_capacitance`, the walls get
+    /// a synthesized resistance-capacitance-resistance construction
+    /// that matches both, and `construction` is ignored.
+    pub wall_overall_u: Option<Float>,
+
+    /// The heat capacity of the walls per unit area, in J/m2K. See `wall_overall_u`.
+    pub wall_areal_capacitance: Option<Float>,
+
+    /// A [`Construction`] built elsewhere, to be used for the walls.
+    ///
+    /// When set, it overrides both `construction` and `wall_overall_u`.
+    /// Its materials and substances are added to the model unless they are already
+    /// there.
+    pub prebuilt_construction: Option<Rc<Construction>>,
+
+    /// The layers of the walls as `(substance, thickness)` pairs, from the
+    /// outside in, for materials that [`TestMat`] does not offer.
+    ///
+    /// When set, it overrides both `construction` and `wall_overall_u` (but not
+    /// `prebuilt_construction`). Each substance is added to the model as given
+    /// (so `emmisivity`, `solar_absorbtance` and `split_layers` do not apply).
+    pub custom_construction: Option<Vec<(NormalSubstance, Float)>>,
+
+    /// The layers of the walls as [`LayerSpec`], from the outside in, for
+    /// bringing tabulated constructions into the builder (see [`from_layer_specs`]).
+    ///
+    /// When set, it overrides both `construction` and `wall_overall_u` (but not
+    /// `prebuilt_construction` or `custom_construction`). Unlike `custom_construction`,
+    /// the substances get the `emmisivity` and `solar_absorbtance`.
+    pub construction_specs: Option<Vec<LayerSpec>>,
+
     /// The surface width
     pub surface_width: Float,
 
     /// The surface height
     pub surface_height: Float,
 
+    /// The number of rows and columns of coplanar patches in which the main
+    /// wall is split (e.g., for detailed radiation tests).
+    ///
+    /// The patches tile the wall exactly and share its construction.
+    /// Walls with a window cannot be subdivided.
+    pub surface_subdivisions: (usize, usize),
+
+    /// The height of the earth bermed against the main wall, in m (zero, the
+    /// default, means no berm).
+    ///
+    /// When positive, the wall is split into a lower "Surface - bermed" segment
+    /// whose front faces [`Boundary::Ground`] and an upper "Surface" segment
+    /// that faces the exterior. It must be smaller than `surface_height`, and the
+    /// window (if any) must be above it.
+    pub berm_height: Float,
+
     /// The window area, will be subtracted from the surface area
     pub window_width: Float,
 
     /// The window height
     pub window_height: Float,
 
+    /// The height of the top of the window above the bottom of the main wall, in m.
+    /// By default (i.e., `None`), the window is centred vertically.
+    ///
+    /// When set, the sill of the window is at `window_head_height - window_height`, and
+    /// the whole window must fit within the wall.
+    pub window_head_height: Option<Float>,
+
+    /// The number of rows and columns in which the window is divided.
+    ///
+    /// When larger than `(1, 1)`, the window becomes a grid of panes
+    /// (each one a [`Fenestration`]) separated by opaque strips of
+    /// [`MUNTIN_WIDTH`], which remain part of the wall. This reduces
+    /// the net glazed area.
+    pub window_divisions: (usize, usize),
+
+    /// Splits the window into two identical windows ("window left" and "window
+    /// right"), mirror images of each other about the centre of the wall and separated by
+    /// a central mullion of [`MUNTIN_WIDTH`]. Together (with the mullion) they span
+    /// `window_width` by `window_height`.
+    ///
+    /// It cannot be combined with `window_divisions`.
+    pub symmetric_windows: bool,
+
+    /// Whether the window is built using the same `construction`
+    /// as the wall (the default). If `false`, `window_construction`
+    /// is required.
+    pub window_uses_wall_construction: bool,
+
+    /// The construction of the window, built out of [`TestMat`]. Only
+    /// used when `window_uses_wall_construction` is `false`
+    pub window_construction: Vec<TestMat>,
+
+    /// A shorthand for the construction of the window. When set, it overrides
+    /// both `window_uses_wall_construction` and `window_construction`.
+    pub glazing: Option<Glazing>,
+
+    /// Multiplies the solar transmittance of every [`TestMat::Glass`] layer of the
+    /// window (i.e., of `glazing` or `window_construction`) to emulate a frit or a
+    /// tint. It must be between 0 and 1, and it cannot be used when the window
+    /// shares the construction of the wall.
+    pub window_shading_coefficient: Option<Float>,
+
     /// The power of the heating in the zone, in W
+    ///
+    /// A heater is added whenever this is not zero. Its sign is not used, since
+    /// the heater cannot be configured for cooling (see [`add_heater`]).
     pub heating_power: Float,
 
     /// The power of the lighting in the wall, in m3.
@@ -106,11 +371,131 @@ pub struct SingleZoneTestBuildingOptions {
     /// The emmisivity of the substances (assigned to all)
     pub emmisivity: Float,
 
+    /// The emmisivity of the exterior face of the [`TestMat`] constructions (i.e., the
+    /// front of their first layer). Defaults to `emmisivity`.
+    ///
+    /// To avoid changing the other layers, the first layer gets its own copy of its substance.
+    pub exterior_emissivity: Option<Float>,
+
+    /// The emmisivity of the interior face of the [`TestMat`] constructions (i.e., the
+    /// back of their last layer). Defaults to `emmisivity`. See `exterior_emissivity`.
+    pub interior_emissivity: Option<Float>,
+
     /// The solar absorbtance of the substances, assigned to all
     pub solar_absorbtance: Float,
 
     /// In degrees. When 0, the exterior points South
     pub orientation: Float,
+
+    /// The boundary on the exterior (i.e., front) side of the main wall.
+    ///
+    /// `simple_model` has no explicit "outdoor" [`Boundary`]: a surface
+    /// whose boundary is not set faces the outdoors. So `None` (the default)
+    /// leaves the front boundary unset, and something like
+    /// `Some(Boundary::Ground)` overrides it.
+    pub exterior_boundary: Option<Boundary>,
+
+    /// Adds a thin space (i.e., a supply plenum) beneath the main zone of
+    /// [`get_single_zone_test_building`], as for underfloor air distribution.
+    ///
+    /// The raised floor is a partition between the two spaces, and the true floor
+    /// of the plenum lies on the ground. See [`FLOOR_PLENUM_HEIGHT`].
+    pub floor_plenum: bool,
+
+    /// Adds a "Floor" to the zone of [`get_single_zone_test_building`], spanning
+    /// its footprint (i.e., `surface_width` by `zone_volume / (surface_width * surface_height)`)
+    /// at `z = 0`, facing down and using the `construction` of the walls. `None`
+    /// (the default) means no floor.
+    ///
+    /// As with every other surface, the back of the floor faces the zone and its front (i.e., its
+    /// exterior) depends on the [`FloorType`]: [`Boundary::Ground`] for [`FloorType::SlabOnGrade`],
+    /// and unset (i.e., outdoors) for [`FloorType::SuspendedVented`]. It cannot be combined with
+    /// `floor_plenum`.
+    pub floor_type: Option<FloorType>,
+
+    /// A linear thermal bridge (e.g., a balcony slab) added to the zone of
+    /// [`get_single_zone_test_building`], as `(length, psi)`: its length in m and
+    /// its linear thermal transmittance in W/mK.
+    ///
+    /// It becomes a "Thermal bridge" surface: a strip `length` long and
+    /// [`THERMAL_BRIDGE_WIDTH`] wide, right below the main wall and facing the same way,
+    /// made of a single [`TestMat::Resistance`] chosen so that the conductance of the strip is
+    /// exactly `length * psi`.
+    pub thermal_bridge: Option<(Float, Float)>,
+
+    /// Gives the zone of [`get_single_zone_test_building`] a cathedral (i.e., sloped)
+    /// ceiling: two exterior surfaces ("Ceiling - South" and "Ceiling - North") that rise
+    /// at [`CATHEDRAL_CEILING_PITCH`] from the top of the walls (at `surface_height`)
+    /// to a ridge that runs parallel to the main wall, halfway through the depth of the zone.
+    ///
+    /// The walls remain rectangular, and the volume of the space becomes `zone_volume` plus
+    /// the volume under the slopes.
+    pub cathedral_ceiling: bool,
+
+    /// A porch (i.e., a covered entry) in front of the window of
+    /// [`get_single_zone_test_building`], as `(depth, height)` in m.
+    ///
+    /// It adds a horizontal "Porch canopy" at `height` and two vertical fins ("Porch fin - East"
+    /// and "Porch fin - West") from the ground up to the canopy, all of them `depth` deep and attached
+    /// to the outside of the main wall. The fins are aligned with the sides of the window, so a window is required.
+    /// These are opaque, exterior-only shading surfaces (i.e., they have no boundaries)
+    /// that use the `construction` of the wall.
+    pub porch: Option<(Float, Float)>,
+
+    /// A horizontal "Overhang" along the whole top of the main wall of
+    /// [`get_single_zone_test_building`] (i.e., at `surface_height`), projecting
+    /// `overhang_depth` m out of it.
+    ///
+    /// As the `porch`, it is an opaque, exterior-only shading surface (i.e., it has no boundaries)
+    /// that uses the `construction` of the wall.
+    pub overhang_depth: Option<Float>,
+
+    /// Adds a "Light shelf" to the zone of [`get_single_zone_test_building`]: a horizontal,
+    /// opaque surface as wide as the window and at the height of its head, projecting
+    /// `light_shelf_depth` m into the zone. A window is required.
+    ///
+    /// Both sides of the shelf face the zone, and it uses the `construction` of the wall.
+    pub light_shelf_depth: Option<Float>,
+
+    /// Adds an "Internal mass" with a total heat capacity of `lumped_capacitance` J/K
+    /// to the zone of [`get_single_zone_test_building`], for reduced-order tests.
+    ///
+    /// The mass is a horizontal surface spanning the footprint of the zone at half its height,
+    /// with both sides facing the zone. It is made of a single layer of a substance with
+    /// [`LUMPED_MASS_PROPERTIES`] whose thickness is `lumped_capacitance / (density * cp * area)`.
+    /// The high conductivity keeps the temperature of the layer nearly uniform.
+    pub lumped_capacitance: Option<Float>,
+
+    /// A free-standing "Neighbor obstruction" (e.g., the building across
+    /// the street) in front of the main wall of [`get_single_zone_test_building`],
+    /// as `(distance, width, height)` in m.
+    ///
+    /// It is a vertical, opaque rectangle `width` by `height` (from the ground up), centred
+    /// on the main wall, parallel to it and `distance` in front of it, facing the wall.
+    /// It is not attached to any space (i.e., it has no boundaries) and it uses the
+    /// `construction` of the wall.
+    pub neighbor_obstruction: Option<(Float, Float, Float)>,
+
+    /// Mirrors the model across the `x = 0` plane (i.e., negates the global `x`
+    /// coordinate of every vertex, after applying `orientation`), for tools that use
+    /// the opposite handedness.
+    ///
+    /// The loops are re-wound, so the normals are mirrored too and still point
+    /// outwards. With `orientation = 0` the main wall keeps facing South, but
+    /// what was East becomes West.
+    pub flip_handedness: bool,
+
+    /// Prepended to the names of every object created from these options
+    /// (e.g., `"Zone 1 - "`), to avoid clashes when several zones share a
+    /// model. Empty by default. See [`add_single_zone_to`].
+    pub name_prefix: String,
+
+    /// The units in which the rest of the fields are given. [`Units::SI`] by default.
+    ///
+    /// [`add_single_zone_to`] (and therefore [`get_single_zone_test_building`]) converts
+    /// the options to SI before building (see [`SingleZoneTestBuildingOptions::in_si`]). The
+    /// rest of the builders only accept SI options.
+    pub units: Units,
 }
 
 impl Default for SingleZoneTestBuildingOptions {
@@ -118,68 +503,262 @@ impl Default for SingleZoneTestBuildingOptions {
         SingleZoneTestBuildingOptions {
             zone_volume: -1., // Will be checked... negative numbers panic
             construction: Vec::with_capacity(0),
+            reverse_layers: false,
+            split_layers: 1,
+            wall_overall_u: None,
+            wall_areal_capacitance: None,
+            prebuilt_construction: None,
+            custom_construction: None,
+            construction_specs: None,
             surface_width: -1.,  // Will be checked... negative numbers panic
             surface_height: -1., // Will be checked... negative numbers panic
+            surface_subdivisions: (1, 1),
+            berm_height: 0.,
             window_width: 0.,
             window_height: 0.,
+            window_head_height: None,
+            window_divisions: (1, 1),
+            symmetric_windows: false,
+            window_uses_wall_construction: true,
+            window_construction: Vec::with_capacity(0),
+            glazing: None,
+            window_shading_coefficient: None,
             heating_power: 0.,
             lighting_power: 0.,
             infiltration_rate: 0.,
             emmisivity: 0.84,
+            exterior_emissivity: None,
+            interior_emissivity: None,
             solar_absorbtance: 0.7,
             orientation: 0.0,
+            exterior_boundary: None,
+            floor_plenum: false,
+            floor_type: None,
+            thermal_bridge: None,
+            cathedral_ceiling: false,
+            porch: None,
+            overhang_depth: None,
+            light_shelf_depth: None,
+            lumped_capacitance: None,
+            neighbor_obstruction: None,
+            flip_handedness: false,
+            name_prefix: String::new(),
+            units: Units::SI,
+        }
+    }
+}
+
+/// Implements a `with_{field}` method for each of the given fields of
+/// [`SingleZoneTestBuildingOptions`], which returns a copy of the options
+/// with that field changed.
+macro_rules! with_methods {
+    ($($method:ident => $field:ident : $t:ty),* $(,)?) => {
+        impl SingleZoneTestBuildingOptions {
+            $(
+                #[doc = concat!("Returns a copy of the options with a different `", stringify!($field), "`")]
+                pub fn $method(&self, $field: $t) -> Self {
+                    let mut ret = self.clone();
+                    ret.$field = $field;
+                    ret
+                }
+            )*
+        }
+    };
+}
+
+with_methods!(
+    with_zone_volume => zone_volume: Float,
+    with_construction => construction: Vec<TestMat>,
+    with_reverse_layers => reverse_layers: bool,
+    with_split_layers => split_layers: usize,
+    with_wall_overall_u => wall_overall_u: Option<Float>,
+    with_wall_areal_capacitance => wall_areal_capacitance: Option<Float>,
+    with_prebuilt_construction => prebuilt_construction: Option<Rc<Construction>>,
+    with_custom_construction => custom_construction: Option<Vec<(NormalSubstance, Float)>>,
+    with_construction_specs => construction_specs: Option<Vec<LayerSpec>>,
+    with_surface_width => surface_width: Float,
+    with_surface_height => surface_height: Float,
+    with_surface_subdivisions => surface_subdivisions: (usize, usize),
+    with_berm_height => berm_height: Float,
+    with_window_width => window_width: Float,
+    with_window_height => window_height: Float,
+    with_window_head_height => window_head_height: Option<Float>,
+    with_window_divisions => window_divisions: (usize, usize),
+    with_symmetric_windows => symmetric_windows: bool,
+    with_window_uses_wall_construction => window_uses_wall_construction: bool,
+    with_window_construction => window_construction: Vec<TestMat>,
+    with_glazing => glazing: Option<Glazing>,
+    with_window_shading_coefficient => window_shading_coefficient: Option<Float>,
+    with_heating_power => heating_power: Float,
+    with_lighting_power => lighting_power: Float,
+    with_infiltration_rate => infiltration_rate: Float,
+    with_emmisivity => emmisivity: Float,
+    with_exterior_emissivity => exterior_emissivity: Option<Float>,
+    with_interior_emissivity => interior_emissivity: Option<Float>,
+    with_solar_absorbtance => solar_absorbtance: Float,
+    with_orientation => orientation: Float,
+    with_exterior_boundary => exterior_boundary: Option<Boundary>,
+    with_floor_plenum => floor_plenum: bool,
+    with_floor_type => floor_type: Option<FloorType>,
+    with_thermal_bridge => thermal_bridge: Option<(Float, Float)>,
+    with_cathedral_ceiling => cathedral_ceiling: bool,
+    with_porch => porch: Option<(Float, Float)>,
+    with_overhang_depth => overhang_depth: Option<Float>,
+    with_light_shelf_depth => light_shelf_depth: Option<Float>,
+    with_lumped_capacitance => lumped_capacitance: Option<Float>,
+    with_neighbor_obstruction => neighbor_obstruction: Option<(Float, Float, Float)>,
+    with_flip_handedness => flip_handedness: bool,
+    with_name_prefix => name_prefix: String,
+    with_units => units: Units,
+);
+
+impl SingleZoneTestBuildingOptions {
+    /// Returns a copy of the options converted to SI units (i.e., with `units` set
+    /// to [`Units::SI`]). SI options are returned unchanged.
+    ///
+    /// When `units` is [`Units::IP`], the fields are interpreted as follows:
+    /// * `zone_volume` in ft3
+    /// * `surface_width`, `surface_height`, `berm_height`, `window_width`,
+    ///   `window_height`, `window_head_height`, `overhang_depth` and `light_shelf_depth` in ft,
+    ///   as are the lengths of `thermal_bridge`, `porch` and `neighbor_obstruction`
+    /// * The thicknesses of the [`TestMat`] in `construction` and `window_construction`
+    ///   in inches, and [`TestMat::Resistance`] in h ft2 F/Btu
+    /// * `wall_overall_u` in Btu/h ft2 F and `wall_areal_capacitance` in Btu/ft2 F
+    /// * `lumped_capacitance` in Btu/F
+    /// * The psi of the `thermal_bridge` in Btu/h ft F
+    /// * `heating_power` and `lighting_power` in Btu/h
+    /// * `infiltration_rate` in ft3/min
+    ///
+    /// Angles and dimensionless fields are not converted, and neither are `prebuilt_construction`,
+    /// `custom_construction` and `construction_specs`, which are already in SI.
+    pub fn in_si(&self) -> Self {
+        if self.units == Units::SI {
+            return self.clone();
+        }
+        let ft = |v: Float| v * M_PER_FT;
+        let layers = |layers: &[TestMat]| -> Vec<TestMat> {
+            layers
+                .iter()
+                .map(|layer| match *layer {
+                    TestMat::Concrete(t) => TestMat::Concrete(t * M_PER_IN),
+                    TestMat::Polyurethane(t) => TestMat::Polyurethane(t * M_PER_IN),
+                    TestMat::Glass(t, tau) => TestMat::Glass(t * M_PER_IN, tau),
+                    TestMat::Air(t) => TestMat::Air(t * M_PER_IN),
+                    TestMat::Resistance(r) => TestMat::Resistance(r * SI_PER_IP_RESISTANCE),
+                })
+                .collect()
+        };
+        SingleZoneTestBuildingOptions {
+            zone_volume: self.zone_volume * M_PER_FT.powi(3),
+            construction: layers(&self.construction),
+            wall_overall_u: self.wall_overall_u.map(|u| u / SI_PER_IP_RESISTANCE),
+            wall_areal_capacitance: self
+                .wall_areal_capacitance
+                .map(|c| c * SI_PER_IP_AREAL_CAPACITANCE),
+            surface_width: ft(self.surface_width),
+            surface_height: ft(self.surface_height),
+            berm_height: ft(self.berm_height),
+            window_width: ft(self.window_width),
+            window_height: ft(self.window_height),
+            window_head_height: self.window_head_height.map(ft),
+            window_construction: layers(&self.window_construction),
+            heating_power: self.heating_power * W_PER_BTU_H,
+            lighting_power: self.lighting_power * W_PER_BTU_H,
+            infiltration_rate: self.infiltration_rate * SI_PER_IP_FLOW,
+            thermal_bridge: self
+                .thermal_bridge
+                .map(|(length, psi)| (ft(length), psi * W_PER_BTU_H / M_PER_FT * 1.8)),
+            porch: self.porch.map(|(d, h)| (ft(d), ft(h))),
+            overhang_depth: self.overhang_depth.map(ft),
+            light_shelf_depth: self.light_shelf_depth.map(ft),
+            lumped_capacitance: self.lumped_capacitance.map(|c| c * SI_PER_IP_CAPACITANCE),
+            neighbor_obstruction: self
+                .neighbor_obstruction
+                .map(|(d, w, h)| (ft(d), ft(w), ft(h))),
+            units: Units::SI,
+            ..self.clone()
         }
     }
 }
 
+/// Prepends `options.name_prefix` to `name`
+pub(crate) fn prefixed(options: &SingleZoneTestBuildingOptions, name: &str) -> String {
+    format!("{}{}", options.name_prefix, name)
+}
+
 /// Adds a luminare to the model
 pub fn add_luminaire(
     model: &mut SimpleModel,
     options: &SingleZoneTestBuildingOptions,
     header: &mut SimulationStateHeader,
+) {
+    let space = Rc::clone(&model.spaces[0]);
+    add_luminaire_to(model, options, header, &space)
+}
+
+/// Adds a luminaire serving `space` to the model
+fn add_luminaire_to(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    header: &mut SimulationStateHeader,
+    space: &Rc<Space>,
 ) {
     let power = options.lighting_power;
     assert!(power > 0.);
-    let mut luminaire = Luminaire::new("the luminaire".to_string());
+    let mut luminaire = Luminaire::new(prefixed(options, "the luminaire"));
     luminaire.set_max_power(power);
-    luminaire.set_target_space(Rc::clone(&model.spaces[0]));
+    luminaire.set_target_space(Rc::clone(space));
     model.add_luminaire(luminaire, header);
 }
 
 /// Adds a heater to the model
+///
+/// The [`ElectricHeater`] does not store a nominal power of its own (its power is the
+/// state element that controllers write into), and it cannot be configured for cooling.
+/// So the only check on `options.heating_power` is that it is not zero (which panics):
+/// positive and negative values produce the same heater.
 pub fn add_heater(
     model: &mut SimpleModel,
     options: &SingleZoneTestBuildingOptions,
     header: &mut SimulationStateHeader,
+) {
+    let space = Rc::clone(&model.spaces[0]);
+    add_heater_to(model, options, header, &space)
+}
+
+/// Adds a heater serving `space` to the model. See [`add_heater`].
+fn add_heater_to(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    header: &mut SimulationStateHeader,
+    space: &Rc<Space>,
 ) {
     let power = options.heating_power;
-    assert!(power > 0.);
-    let mut hvac = ElectricHeater::new("some hvac".to_string());
-    hvac.set_target_space(Rc::clone(&model.spaces[0]));
+    assert!(power != 0.);
+    let mut hvac = ElectricHeater::new(prefixed(options, "some hvac"));
+    hvac.set_target_space(Rc::clone(space));
     model.add_hvac(hvac.wrap(), header);
 }
 
-/// A single space model with a single surface (optionally) one operable window that has the same construction
-/// as the rest of the walls. Thw front of the surface faces South.
-///
-/// The surface_area includes the window; the window_area is cut down from it.
-pub fn get_single_zone_test_building(
+/// Adds a [`Space`] to the model, with the infiltration described
+/// in the `options` (if any).
+pub(crate) fn add_space(
+    model: &mut SimpleModel,
     options: &SingleZoneTestBuildingOptions,
-) -> (SimpleModel, SimulationStateHeader) {
-    let mut model = SimpleModel::new("The SimpleModel".to_string());
-    let mut header = SimulationStateHeader::new();
-
-    /*************** */
-    /* ADD THE SPACE */
-    /*************** */
-    let zone_volume = options.zone_volume;
+    name: &str,
+    volume: Float,
+) -> Rc<Space> {
+    assert!(
+        options.units == Units::SI,
+        "Only add_single_zone_to and get_single_zone_test_building accept options in IP units"
+    );
     assert!(
-        zone_volume > 0.0,
+        volume > 0.0,
         "A positive zone_volume parameter is required (Float)"
     );
 
-    let mut space = Space::new("Some space".to_string());
-    space.set_volume(zone_volume);
+    let mut space = Space::new(prefixed(options, name));
+    space.set_volume(volume);
 
     /*********************** */
     /* ADD INFILTRATION, IF NEEDED */
@@ -192,188 +771,1152 @@ pub fn get_single_zone_test_building(
     }
 
     // .set_importance(Box::new(ScheduleConstant::new(1.0)));
-    let space = model.add_space(space);
-
-    /******************* */
-    /* ADD THE SUBSTANCE */
-    /******************* */
-
-    // Add both substances
-    let mut concrete = NormalSubstance::new("concrete".to_string());
-    concrete
-        .set_density(1700.)
-        .set_specific_heat_capacity(800.)
-        .set_thermal_conductivity(0.816)
-        .set_front_thermal_absorbtance(options.emmisivity)
-        .set_back_thermal_absorbtance(options.emmisivity)
-        .set_front_solar_absorbtance(options.solar_absorbtance)
-        .set_back_solar_absorbtance(options.solar_absorbtance);
-    let concrete = model.add_substance(concrete.wrap());
+    model.add_space(space)
+}
+
+/// Adds the substances and materials described in `options.construction`
+/// to the model, and returns the resulting [`Construction`]
+pub(crate) fn add_construction(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+) -> Rc<Construction> {
+    if let Some(construction) = &options.prebuilt_construction {
+        return register_construction(model, construction);
+    }
+    if let Some(layers) = &options.custom_construction {
+        return add_custom_construction(model, options, layers);
+    }
+    if let Some(layers) = &options.construction_specs {
+        return from_layer_specs(model, options, layers);
+    }
+    match (options.wall_overall_u, options.wall_areal_capacitance) {
+        (None, None) => {
+            let mut layers = options.construction.clone();
+            if options.reverse_layers {
+                layers.reverse();
+            }
+            add_layers(model, options, "the construction", "Material", &layers)
+        }
+        (Some(u_value), Some(capacitance)) => {
+            add_rc_construction(model, options, u_value, capacitance)
+        }
+        _ => panic!("wall_overall_u and wall_areal_capacitance need to be set together"),
+    }
+}
+
+/// Adds the substances in `layers` to the model, and returns a [`Construction`]
+/// with one material per layer (named as in [`add_layers`])
+fn add_custom_construction(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    layers: &[(NormalSubstance, Float)],
+) -> Rc<Construction> {
+    assert!(
+        !layers.is_empty(),
+        "custom_construction needs at least one layer"
+    );
+    let mut construction = Construction::new(prefixed(options, "the construction"));
+    for (i, (substance, thickness)) in layers.iter().enumerate() {
+        assert!(
+            *thickness > 0.0,
+            "The layers of custom_construction need a positive thickness"
+        );
+        let substance = model.add_substance(substance.clone().wrap());
+        let name = prefixed(options, &format!("Material {}", i));
+        let material = model.add_material(Material::new(name, substance, *thickness));
+        construction.materials.push(material);
+    }
+    model.add_construction(construction)
+}
+
+/// Adds one substance and one material (both named after the layer) per [`LayerSpec`]
+/// to the model, and returns the resulting construction (called "the construction").
+///
+/// The substances get the `emmisivity` and `solar_absorbtance` of the `options`, and
+/// every name gets their `name_prefix`.
+pub fn from_layer_specs(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    layers: &[LayerSpec],
+) -> Rc<Construction> {
+    assert!(
+        !layers.is_empty(),
+        "construction_specs needs at least one layer"
+    );
+    let mut construction = Construction::new(prefixed(options, "the construction"));
+    for layer in layers.iter() {
+        assert!(
+            layer.thickness > 0.0 && layer.conductivity > 0.0,
+            "Layer '{}' needs a positive thickness and conductivity",
+            layer.name
+        );
+        let material = add_normal_material(
+            model,
+            options,
+            &layer.name,
+            layer.thickness,
+            (layer.density, layer.specific_heat, layer.conductivity),
+        );
+        construction.materials.push(material);
+    }
+    model.add_construction(construction)
+}
+
+/// Adds an existing [`Construction`] to the model, together with those
+/// of its materials and substances that are not already in it.
+fn register_construction(
+    model: &mut SimpleModel,
+    construction: &Rc<Construction>,
+) -> Rc<Construction> {
+    for material in construction.materials.iter() {
+        if !model
+            .substances
+            .iter()
+            .any(|s| Rc::ptr_eq(s, &material.substance))
+        {
+            model.substances.push(Rc::clone(&material.substance));
+        }
+        if !model.materials.iter().any(|m| Rc::ptr_eq(m, material)) {
+            model.materials.push(Rc::clone(material));
+        }
+    }
+    if !model
+        .constructions
+        .iter()
+        .any(|c| Rc::ptr_eq(c, construction))
+    {
+        model.constructions.push(Rc::clone(construction));
+    }
+    Rc::clone(construction)
+}
+
+/// The thickness of the massive core of the constructions synthesized
+/// from `wall_overall_u` and `wall_areal_capacitance`, in m
+const RC_CORE_THICKNESS: Float = 0.1;
 
-    let mut polyurethane = NormalSubstance::new("polyurethane".to_string());
-    polyurethane
-        .set_density(17.5)
-        .set_specific_heat_capacity(2400.)
-        .set_thermal_conductivity(0.0252)
+/// The thermal conductivity of the massive core of the constructions
+/// synthesized from `wall_overall_u` and `wall_areal_capacitance`, in W/mK
+const RC_CORE_CONDUCTIVITY: Float = 100.;
+
+/// Adds a [`Material`] made of a new [`NormalSubstance`] with the given properties
+fn add_normal_material(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    name: &str,
+    thickness: Float,
+    (density, specific_heat, conductivity): (Float, Float, Float),
+) -> Rc<Material> {
+    let mut substance = NormalSubstance::new(prefixed(options, name));
+    substance
+        .set_density(density)
+        .set_specific_heat_capacity(specific_heat)
+        .set_thermal_conductivity(conductivity)
         .set_front_thermal_absorbtance(options.emmisivity)
         .set_back_thermal_absorbtance(options.emmisivity)
         .set_front_solar_absorbtance(options.solar_absorbtance)
         .set_back_solar_absorbtance(options.solar_absorbtance);
+    let substance = model.add_substance(substance.wrap());
+    model.add_material(Material::new(prefixed(options, name), substance, thickness))
+}
+
+/// Synthesizes a resistance-capacitance-resistance construction with
+/// an overall U-value of `u_value` (W/m2K) and a heat capacity of
+/// `capacitance` (J/m2K).
+///
+/// The three layers are:
+/// * An outer resistive layer, like [`TestMat::Resistance`], of `R_1`
+/// * A massive core of thickness `t_c = 0.1` m and conductivity `k_c = 100` W/mK
+///   (so its resistance is `R_c = t_c/k_c`), with a specific heat of 1000 J/kgK
+///   and a density of `(C - 2·C_r) / (1000·t_c)`
+/// * An inner resistive layer of `R_1`
+///
+/// Where `R_1 = (1/U - R_c)/2` and `C_r` is the (tiny) heat capacity of
+/// a resistive layer. Thus, the layers add up to exactly `1/U` and `C`.
+fn add_rc_construction(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    u_value: Float,
+    capacitance: Float,
+) -> Rc<Construction> {
+    assert!(u_value > 0.0, "A positive wall_overall_u is required");
+    // Resistive layers are built like TestMat::Resistance
+    let (resistive_density, resistive_cp) = (1., 1000.);
+    let resistive_capacitance = resistive_density * resistive_cp * RESISTANCE_LAYER_THICKNESS;
+    let core_capacitance = capacitance - 2. * resistive_capacitance;
+    assert!(
+        core_capacitance > 0.0,
+        "wall_areal_capacitance needs to be larger than {} J/m2K",
+        2. * resistive_capacitance
+    );
+    let core_resistance = RC_CORE_THICKNESS / RC_CORE_CONDUCTIVITY;
+    let side_resistance = (1. / u_value - core_resistance) / 2.;
+    assert!(
+        side_resistance > 0.0,
+        "wall_overall_u needs to be smaller than {} W/m2K",
+        1. / core_resistance
+    );
+
+    let side = (
+        resistive_density,
+        resistive_cp,
+        RESISTANCE_LAYER_THICKNESS / side_resistance,
+    );
+    let core_cp = 1000.;
+    let core = (
+        core_capacitance / (core_cp * RC_CORE_THICKNESS),
+        core_cp,
+        RC_CORE_CONDUCTIVITY,
+    );
+
+    let mut construction = Construction::new(prefixed(options, "the construction"));
+    construction.materials = vec![
+        add_normal_material(
+            model,
+            options,
+            "Material 0",
+            RESISTANCE_LAYER_THICKNESS,
+            side,
+        ),
+        add_normal_material(model, options, "Material 1", RC_CORE_THICKNESS, core),
+        add_normal_material(
+            model,
+            options,
+            "Material 2",
+            RESISTANCE_LAYER_THICKNESS,
+            side,
+        ),
+    ];
+    model.add_construction(construction)
+}
+
+/// Splits each of the `layers` into `n` equal sublayers (see `split_layers`)
+fn split_layers(layers: &[TestMat], n: usize) -> Vec<TestMat> {
+    assert!(n > 0, "split_layers needs to be at least 1");
+    let f = n as Float;
+    layers
+        .iter()
+        .flat_map(|layer| {
+            let sublayer = match layer {
+                TestMat::Concrete(t) => TestMat::Concrete(t / f),
+                TestMat::Polyurethane(t) => TestMat::Polyurethane(t / f),
+                TestMat::Glass(t, tau) => TestMat::Glass(t / f, *tau),
+                TestMat::Resistance(r) => TestMat::Resistance(r / f),
+                TestMat::Air(t) => return vec![TestMat::Air(*t)],
+            };
+            vec![sublayer; n]
+        })
+        .collect()
+}
+
+/// Registers `substance` in the model as the substance of the material called
+/// `material_name`. If a `front` or `back` thermal absorbtance (i.e., emmisivity) is given,
+/// the substance is first renamed after the material and given these absorbtances.
+fn add_face_substance(
+    model: &mut SimpleModel,
+    mut substance: NormalSubstance,
+    material_name: &str,
+    front: Option<Float>,
+    back: Option<Float>,
+) -> Rc<Substance> {
+    if front.is_some() || back.is_some() {
+        substance.name = format!("{} - {}", substance.name, material_name);
+    }
+    if let Some(front) = front {
+        substance.set_front_thermal_absorbtance(front);
+    }
+    if let Some(back) = back {
+        substance.set_back_thermal_absorbtance(back);
+    }
+    model.add_substance(substance.wrap())
+}
+
+/// Adds the substances and materials described by `layers` to the model,
+/// and returns the resulting [`Construction`] called `name`. The materials are
+/// named `"{material_prefix} {i}"`.
+///
+/// Only the substances used by some layer are added: the layers that get their own
+/// `exterior_emissivity` or `interior_emissivity` use a copy of the shared substance, so
+/// they do not register the shared one.
+pub(crate) fn add_layers(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    name: &str,
+    material_prefix: &str,
+    layers: &[TestMat],
+) -> Rc<Construction> {
+    let material_prefix = prefixed(options, material_prefix);
+    let layers = split_layers(layers, options.split_layers);
 
-    let polyurethane = model.add_substance(polyurethane.wrap());
+    /******************************************** */
+    /* THE SHARED SUBSTANCES, ADDED WHEN NEEDED */
+    /******************************************** */
+    let normal = |name: &str, density: Float, specific_heat: Float, conductivity: Float| {
+        let mut substance = NormalSubstance::new(prefixed(options, name));
+        substance
+            .set_density(density)
+            .set_specific_heat_capacity(specific_heat)
+            .set_thermal_conductivity(conductivity)
+            .set_front_thermal_absorbtance(options.emmisivity)
+            .set_back_thermal_absorbtance(options.emmisivity)
+            .set_front_solar_absorbtance(options.solar_absorbtance)
+            .set_back_solar_absorbtance(options.solar_absorbtance);
+        substance
+    };
+    let concrete_substance = || normal("concrete", 1700., 800., 0.816);
+    let polyurethane_substance = || normal("polyurethane", 17.5, 2400., 0.0252);
 
-    let mut air = Gas::new("some_gas".to_string());
-    air.set_gas(StandardGas::Air);
-    let air = model.add_substance(air.wrap());
+    let mut concrete: Option<Rc<Substance>> = None;
+    let mut polyurethane: Option<Rc<Substance>> = None;
+    let mut air: Option<Rc<Substance>> = None;
 
     /*********************************** */
     /* ADD THE MATERIAL AND CONSTRUCTION */
     /*********************************** */
-    let mut construction = Construction::new("the construction".to_string());
-    for (i, c) in options.construction.iter().enumerate() {
-        let material = match c {
+    let mut construction = Construction::new(prefixed(options, name));
+    for (i, c) in layers.iter().enumerate() {
+        let material_name = format!("{} {}", material_prefix, i);
+        let front = options.exterior_emissivity.filter(|_| i == 0);
+        let back = options
+            .interior_emissivity
+            .filter(|_| i + 1 == layers.len());
+        let own = front.is_some() || back.is_some();
+
+        let (substance, thickness) = match c {
             TestMat::Concrete(thickness) => {
-                Material::new(format!("Material {}", i), concrete.clone(), *thickness)
+                let substance = if own {
+                    add_face_substance(model, concrete_substance(), &material_name, front, back)
+                } else {
+                    let shared = concrete
+                        .get_or_insert_with(|| model.add_substance(concrete_substance().wrap()));
+                    Rc::clone(shared)
+                };
+                (substance, *thickness)
             }
             TestMat::Polyurethane(thickness) => {
-                Material::new(format!("Material {}", i), polyurethane.clone(), *thickness)
+                let substance = if own {
+                    add_face_substance(model, polyurethane_substance(), &material_name, front, back)
+                } else {
+                    let shared = polyurethane.get_or_insert_with(|| {
+                        model.add_substance(polyurethane_substance().wrap())
+                    });
+                    Rc::clone(shared)
+                };
+                (substance, *thickness)
             }
             TestMat::Glass(thickness, solar_transmittance) => {
-                let mut glass = NormalSubstance::new("polyurethane".to_string());
-                glass
-                    .set_density(2.5)
-                    .set_specific_heat_capacity(840.)
-                    .set_thermal_conductivity(1.)
-                    .set_front_thermal_absorbtance(options.emmisivity)
-                    .set_back_thermal_absorbtance(options.emmisivity)
-                    .set_front_solar_absorbtance(options.solar_absorbtance)
-                    .set_back_solar_absorbtance(options.solar_absorbtance)
-                    .set_solar_transmittance(*solar_transmittance);
-                let glass = model.add_substance(glass.wrap());
-                Material::new(format!("Material {}", i), glass, *thickness)
+                let mut glass = normal("polyurethane", 2.5, 840., 1.);
+                glass.set_solar_transmittance(*solar_transmittance);
+                let glass = add_face_substance(model, glass, &material_name, front, back);
+                (glass, *thickness)
             }
             TestMat::Air(thickness) => {
-                Material::new(format!("Material {}", i), air.clone(), *thickness)
+                // Gases have no emmisivity, so they are always shared
+                let shared = air.get_or_insert_with(|| {
+                    let mut air = Gas::new(prefixed(options, "some_gas"));
+                    air.set_gas(StandardGas::Air);
+                    model.add_substance(air.wrap())
+                });
+                (Rc::clone(shared), *thickness)
+            }
+            TestMat::Resistance(r_value) => {
+                assert!(
+                    *r_value > 0.0,
+                    "A positive thermal resistance is required (Float)"
+                );
+                let resistance = normal(
+                    &format!("resistance {}", i),
+                    1.,
+                    1000.,
+                    RESISTANCE_LAYER_THICKNESS / r_value,
+                );
+                let resistance = add_face_substance(model, resistance, &material_name, front, back);
+                (resistance, RESISTANCE_LAYER_THICKNESS)
             }
         };
-        let material = model.add_material(material);
+        let material = model.add_material(Material::new(material_name, substance, thickness));
         construction.materials.push(material);
     }
-    let construction = model.add_construction(construction);
+    model.add_construction(construction)
+}
 
-    /****************** */
-    /* SURFACE GEOMETRY */
-    /****************** */
-    // Wall
-    assert!(
-        options.surface_width > 0.0 && options.surface_height > 0.0,
-        "A positive surface_area option is needed (Float)"
-    );
+/// Adds the heater and the luminaire serving `space` to the model, if the `options`
+/// ask for them.
+pub(crate) fn add_loads(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    header: &mut SimulationStateHeader,
+    space: &Rc<Space>,
+) {
+    /*********************** */
+    /* ADD HEATER, IF NEEDED */
+    /*********************** */
+    if options.heating_power != 0.0 {
+        add_heater_to(model, options, header, space);
+    }
 
-    let l = options.surface_width / 2.;
-    let mut the_loop = Loop3D::new();
-    let angle = options.orientation.to_radians();
-
-    the_loop
-        .push(Point3D::new(-l * angle.cos(), -l * angle.sin(), 0.))
-        .unwrap();
-    the_loop
-        .push(Point3D::new(l * angle.cos(), l * angle.sin(), 0.))
-        .unwrap();
-    the_loop
-        .push(Point3D::new(
-            l * angle.cos(),
-            l * angle.sin(),
-            options.surface_height,
-        ))
-        .unwrap();
-    the_loop
-        .push(Point3D::new(
-            -l * angle.cos(),
-            -l * angle.sin(),
-            options.surface_height,
-        ))
-        .unwrap();
-    the_loop.close().unwrap();
+    /*********************** */
+    /* ADD LIGHTS, IF NEEDED */
+    /*********************** */
+    if options.lighting_power > 0.0 {
+        add_luminaire_to(model, options, header, space);
+    }
+}
 
-    let mut p = Polygon3D::new(the_loop).unwrap();
+/// The range of `heating_power` (in W) produced by [`randomize_loads`]
+pub const RANDOM_HEATING_POWER: (Float, Float) = (0., 3000.);
 
-    // Window... if there is any
-    let mut window_polygon: Option<Polygon3D> = None;
-    if options.window_width > 0.0 && options.window_height > 0.0 {
-        assert!(
-            options.window_width * options.window_height
-                < options.surface_width * options.surface_height,
-            "Win_area >= Surface_area"
-        );
+/// The range of `lighting_power` (in W) produced by [`randomize_loads`]
+pub const RANDOM_LIGHTING_POWER: (Float, Float) = (0., 500.);
 
-        let l = options.window_width / 2.;
-        let mut the_inner_loop = Loop3D::new();
-        the_inner_loop
-            .push(Point3D::new(-l * angle.cos(), -l * angle.sin(), options.surface_height/2. - options.window_height/2.))
-            .unwrap();
-        the_inner_loop
-            .push(Point3D::new(l * angle.cos(), l * angle.sin(), options.surface_height/2. - options.window_height/2.))
-            .unwrap();
-        the_inner_loop
-            .push(Point3D::new(l * angle.cos(), l * angle.sin(), options.surface_height/2. + options.window_height/2.))
-            .unwrap();
-        the_inner_loop
-            .push(Point3D::new(
-                -l * angle.cos(),
-                -l * angle.sin(),
-                options.surface_height/2. + options.window_height/2.,
-            ))
-            .unwrap();
-        the_inner_loop.close().unwrap();
-        p.cut_hole(the_inner_loop.clone()).unwrap();
-        window_polygon = Some(Polygon3D::new(the_inner_loop).unwrap());
+/// Returns a copy of the `options` with random loads, leaving the geometry
+/// and constructions untouched.
+///
+/// The `heating_power` and the `lighting_power` are drawn uniformly from
+/// [`RANDOM_HEATING_POWER`] and [`RANDOM_LIGHTING_POWER`]. The same `seed`
+/// always produces the same loads. (`simple_model` has no equipment or
+/// occupancy loads, so there is nothing else to randomize.)
+pub fn randomize_loads(
+    options: &SingleZoneTestBuildingOptions,
+    seed: u64,
+) -> SingleZoneTestBuildingOptions {
+    let mut state = seed;
+    // SplitMix64, which is good enough for tests and needs no dependencies
+    let mut next = |(min, max): (Float, Float)| {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        let unit = (z >> 11) as f64 / (1u64 << 53) as f64;
+        min + (max - min) * unit as Float
+    };
+
+    SingleZoneTestBuildingOptions {
+        heating_power: next(RANDOM_HEATING_POWER),
+        lighting_power: next(RANDOM_LIGHTING_POWER),
+        ..options.clone()
+    }
+}
+
+/// Returns the construction of the windows: a new one built from `options.glazing`
+/// (if set), or either the `construction` of the walls or a new one built
+/// from `options.window_construction`, depending on `options.window_uses_wall_construction`.
+pub(crate) fn add_window_construction(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    construction: &Rc<Construction>,
+) -> Rc<Construction> {
+    let layers = if let Some(glazing) = options.glazing {
+        glazing.layers()
+    } else if options.window_uses_wall_construction {
+        assert!(
+            options.window_shading_coefficient.is_none(),
+            "window_shading_coefficient requires a glazing or a window_construction"
+        );
+        return Rc::clone(construction);
+    } else {
+        assert!(
+            !options.window_construction.is_empty(),
+            "A window_construction is required when window_uses_wall_construction is false"
+        );
+        options.window_construction.clone()
+    };
+
+    let layers: Vec<TestMat> = match options.window_shading_coefficient {
+        None => layers,
+        Some(coefficient) => {
+            assert!(
+                (0.0..=1.0).contains(&coefficient),
+                "window_shading_coefficient needs to be between 0 and 1... found {}",
+                coefficient
+            );
+            layers
+                .into_iter()
+                .map(|layer| match layer {
+                    TestMat::Glass(t, tau) => TestMat::Glass(t, tau * coefficient),
+                    other => other,
+                })
+                .collect()
+        }
+    };
+    add_layers(
+        model,
+        options,
+        "the window construction",
+        "Window material",
+        &layers,
+    )
+}
+
+/// The width of the frame strips (i.e., muntins) that separate the
+/// panes of a window when `window_divisions` is larger than `(1, 1)`, in m
+pub const MUNTIN_WIDTH: Float = 0.02;
+
+/// The height of the bottom of the window in the main wall, which is centred
+/// vertically unless `options.window_head_height` is set
+fn window_sill(options: &SingleZoneTestBuildingOptions) -> Float {
+    match options.window_head_height {
+        None => options.surface_height / 2. - options.window_height / 2.,
+        Some(head) => {
+            let sill = head - options.window_height;
+            assert!(
+                sill >= 0.0 && head <= options.surface_height,
+                "The window does not fit in the wall with a window_head_height of {}",
+                head
+            );
+            sill
+        }
+    }
+}
+
+/// Builds the loops of the glazed panes of the window in the main wall,
+/// with their names. This is empty if there is no window.
+fn window_panes(options: &SingleZoneTestBuildingOptions) -> Vec<(String, Loop3D)> {
+    if options.window_width <= 0.0 || options.window_height <= 0.0 {
+        return Vec::new();
+    }
+    assert!(
+        options.window_width * options.window_height
+            < options.surface_width * options.surface_height,
+        "Win_area >= Surface_area"
+    );
+
+    let (rows, columns) = if options.symmetric_windows {
+        assert!(
+            options.window_divisions == (1, 1),
+            "symmetric_windows cannot be combined with window_divisions"
+        );
+        (1, 2)
+    } else {
+        options.window_divisions
+    };
+    assert!(
+        rows > 0 && columns > 0,
+        "window_divisions needs at least one row and one column"
+    );
+    if rows == 1 && columns == 1 {
+        let l = options.window_width / 2.;
+        let sill = window_sill(options);
+        let the_inner_loop = vertical_rectangle(
+            options.orientation,
+            (-l, 0.),
+            (l, 0.),
+            sill,
+            sill + options.window_height,
+        );
+        return vec![(prefixed(options, "window one"), the_inner_loop)];
+    }
+
+    let pane_width =
+        (options.window_width - (columns - 1) as Float * MUNTIN_WIDTH) / columns as Float;
+    let pane_height = (options.window_height - (rows - 1) as Float * MUNTIN_WIDTH) / rows as Float;
+    assert!(
+        pane_width > 0.0 && pane_height > 0.0,
+        "The window is too small to be divided into {} rows and {} columns",
+        rows,
+        columns
+    );
+
+    let left = -options.window_width / 2.;
+    let sill = window_sill(options);
+    let mut panes = Vec::with_capacity(rows * columns);
+    for row in 0..rows {
+        let z = sill + row as Float * (pane_height + MUNTIN_WIDTH);
+        for col in 0..columns {
+            let x = left + col as Float * (pane_width + MUNTIN_WIDTH);
+            let the_loop = vertical_rectangle(
+                options.orientation,
+                (x, 0.),
+                (x + pane_width, 0.),
+                z,
+                z + pane_height,
+            );
+            let name = if options.symmetric_windows {
+                prefixed(
+                    options,
+                    if col == 0 {
+                        "window left"
+                    } else {
+                        "window right"
+                    },
+                )
+            } else {
+                prefixed(options, &format!("window one - pane {}-{}", row, col))
+            };
+            panes.push((name, the_loop));
+        }
+    }
+    panes
+}
+
+/// Adds the main wall of the model (i.e., the one at the origin, which faces
+/// South when `orientation` is zero), with its window (if any), to the
+/// `space`. Its front faces the outdoors, unless `options.exterior_boundary`
+/// says otherwise.
+///
+/// The window uses `window_construction` or, if it is `None`, a new one built
+/// by [`add_window_construction`]. Presets that glaze other surfaces
+/// pass theirs, so the model holds a single window construction.
+pub(crate) fn add_main_wall(
+    model: &mut SimpleModel,
+    header: &mut SimulationStateHeader,
+    options: &SingleZoneTestBuildingOptions,
+    space: &Rc<Space>,
+    construction: &Rc<Construction>,
+    window_construction: Option<&Rc<Construction>>,
+) {
+    assert!(
+        options.glazing.is_some()
+            || options.window_uses_wall_construction
+            || !options.window_construction.is_empty(),
+        "A window_construction is required when window_uses_wall_construction is false"
+    );
+
+    /****************** */
+    /* SURFACE GEOMETRY */
+    /****************** */
+    // Wall
+    assert!(
+        options.surface_width > 0.0 && options.surface_height > 0.0,
+        "A positive surface_area option is needed (Float)"
+    );
+
+    let l = options.surface_width / 2.;
+
+    // Patches... if the wall is subdivided
+    let (rows, columns) = options.surface_subdivisions;
+    assert!(
+        rows > 0 && columns > 0,
+        "surface_subdivisions needs at least one row and one column"
+    );
+    if rows > 1 || columns > 1 {
+        assert!(
+            window_panes(options).is_empty(),
+            "Walls with a window cannot be subdivided"
+        );
+        assert!(
+            options.berm_height == 0.0,
+            "Bermed walls cannot be subdivided"
+        );
+        let patch_width = options.surface_width / columns as Float;
+        let patch_height = options.surface_height / rows as Float;
+        for row in 0..rows {
+            let z = row as Float * patch_height;
+            for col in 0..columns {
+                let x = -l + col as Float * patch_width;
+                let the_loop = vertical_rectangle(
+                    options.orientation,
+                    (x, 0.),
+                    (x + patch_width, 0.),
+                    z,
+                    z + patch_height,
+                );
+                let mut surface = Surface::new(
+                    prefixed(options, &format!("Surface - patch {}-{}", row, col)),
+                    Polygon3D::new(the_loop).unwrap(),
+                    Rc::clone(construction),
+                );
+                surface.set_back_boundary(Boundary::Space(Rc::clone(space)));
+                if let Some(boundary) = &options.exterior_boundary {
+                    surface.set_front_boundary(boundary.clone());
+                }
+                model.add_surface(surface);
+            }
+        }
+        return;
+    }
+
+    // Berm... if there is any
+    let berm = options.berm_height;
+    assert!(
+        berm >= 0.0 && berm < options.surface_height,
+        "berm_height must be non-negative and smaller than surface_height"
+    );
+    let panes = window_panes(options);
+    if berm > 0.0 {
+        assert!(
+            panes.is_empty() || window_sill(options) >= berm,
+            "The window must be above the berm"
+        );
+        let the_loop = vertical_rectangle(options.orientation, (-l, 0.), (l, 0.), 0., berm);
+        let mut surface = Surface::new(
+            prefixed(options, "Surface - bermed"),
+            Polygon3D::new(the_loop).unwrap(),
+            Rc::clone(construction),
+        );
+        surface.set_back_boundary(Boundary::Space(Rc::clone(space)));
+        surface.set_front_boundary(Boundary::Ground);
+        model.add_surface(surface);
+    }
+
+    let the_loop = vertical_rectangle(
+        options.orientation,
+        (-l, 0.),
+        (l, 0.),
+        berm,
+        options.surface_height,
+    );
+
+    let mut p = Polygon3D::new(the_loop).unwrap();
+
+    // Window... if there is any
+    for (_, pane) in panes.iter() {
+        p.cut_hole(pane.clone()).unwrap();
     }
 
     /***************** */
     /* ACTUAL SURFACES */
     /***************** */
     // Add surface
-    let mut surface = Surface::new("Surface".to_string(), p, Rc::clone(&construction));
-    surface.set_back_boundary(Boundary::Space(Rc::clone(&space)));
+    let mut surface = Surface::new(prefixed(options, "Surface"), p, Rc::clone(construction));
+    surface.set_back_boundary(Boundary::Space(Rc::clone(space)));
+    if let Some(boundary) = &options.exterior_boundary {
+        surface.set_front_boundary(boundary.clone());
+    }
     model.add_surface(surface);
 
     // Add window.
-    if let Some(window_polygon) = window_polygon {
+    if panes.is_empty() {
+        return;
+    }
+    let window_construction = match window_construction {
+        Some(c) => Rc::clone(c),
+        None => add_window_construction(model, options, construction),
+    };
+    for (name, pane) in panes {
         let mut fenestration = Fenestration::new(
-            "window one".to_string(),
-            window_polygon,
-            construction,
+            name,
+            Polygon3D::new(pane).unwrap(),
+            Rc::clone(&window_construction),
             FenestrationPositions::Binary,
             FenestrationType::Window,
         );
 
-        fenestration.set_front_boundary(Boundary::Space(Rc::clone(&space)));
-        model.add_fenestration(fenestration, &mut header);
+        fenestration.set_front_boundary(Boundary::Space(Rc::clone(space)));
+        model.add_fenestration(fenestration, header);
     }
+}
 
-    /*********************** */
-    /* ADD HEATER, IF NEEDED */
-    /*********************** */
-    if options.heating_power > 0.0 {
-        add_heater(&mut model, options, &mut header);
+/// The width of the strip that represents the `thermal_bridge`, in m
+pub const THERMAL_BRIDGE_WIDTH: Float = 0.01;
+
+/// Adds the canopy and the fins of `options.porch` (if any) to the model
+fn add_porch(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    construction: &Rc<Construction>,
+) {
+    let (depth, height) = match options.porch {
+        Some(porch) => porch,
+        None => return,
+    };
+    assert!(
+        depth > 0.0 && height > 0.0,
+        "A porch needs a positive depth and height"
+    );
+    assert!(
+        options.window_width > 0.0 && options.window_height > 0.0,
+        "A porch needs a window to stand in front of"
+    );
+    let o = options.orientation;
+    let l = options.window_width / 2.;
+    let surfaces = [
+        (
+            "Porch canopy",
+            horizontal_rectangle(o, (-l, -depth), (l, 0.), height, true),
+        ),
+        (
+            "Porch fin - East",
+            vertical_rectangle(o, (l, -depth), (l, 0.), 0., height),
+        ),
+        (
+            "Porch fin - West",
+            vertical_rectangle(o, (-l, 0.), (-l, -depth), 0., height),
+        ),
+    ];
+    for (name, the_loop) in surfaces {
+        model.add_surface(Surface::new(
+            prefixed(options, name),
+            Polygon3D::new(the_loop).unwrap(),
+            Rc::clone(construction),
+        ));
     }
+}
 
-    /*********************** */
-    /* ADD LIGHTS, IF NEEDED */
-    /*********************** */
-    if options.lighting_power > 0.0 {
-        add_luminaire(&mut model, options, &mut header);
+/// Adds the `options.overhang_depth` (if requested) to the model
+fn add_overhang(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    construction: &Rc<Construction>,
+) {
+    let depth = match options.overhang_depth {
+        Some(depth) => depth,
+        None => return,
+    };
+    assert!(depth > 0.0, "An overhang needs a positive depth");
+    let l = options.surface_width / 2.;
+    let the_loop = horizontal_rectangle(
+        options.orientation,
+        (-l, -depth),
+        (l, 0.),
+        options.surface_height,
+        true,
+    );
+    model.add_surface(Surface::new(
+        prefixed(options, "Overhang"),
+        Polygon3D::new(the_loop).unwrap(),
+        Rc::clone(construction),
+    ));
+}
+
+/// Adds the floor described by `options.floor_type` (if any) to `space`
+fn add_floor(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    space: &Rc<Space>,
+    construction: &Rc<Construction>,
+) {
+    let floor_type = match options.floor_type {
+        Some(floor_type) => floor_type,
+        None => return,
+    };
+    assert!(
+        !options.floor_plenum,
+        "floor_type cannot be combined with floor_plenum"
+    );
+    let l = options.surface_width / 2.;
+    let the_loop = horizontal_rectangle(
+        options.orientation,
+        (-l, 0.),
+        (l, zone_depth(options)),
+        0.,
+        false,
+    );
+    let mut surface = Surface::new(
+        prefixed(options, "Floor"),
+        Polygon3D::new(the_loop).unwrap(),
+        Rc::clone(construction),
+    );
+    surface.set_back_boundary(Boundary::Space(Rc::clone(space)));
+    if floor_type == FloorType::SlabOnGrade {
+        surface.set_front_boundary(Boundary::Ground);
     }
+    model.add_surface(surface);
+}
+
+/// The density (kg/m3), specific heat capacity (J/kgK) and thermal conductivity (W/mK)
+/// of the substance of the `lumped_capacitance`
+pub const LUMPED_MASS_PROPERTIES: (Float, Float, Float) = (1000., 1000., 1000.);
 
-    // Return
+/// Adds the internal mass representing `options.lumped_capacitance` (if any) to `space`
+fn add_lumped_mass(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    space: &Rc<Space>,
+) {
+    let capacitance = match options.lumped_capacitance {
+        Some(capacitance) => capacitance,
+        None => return,
+    };
+    assert!(
+        capacitance > 0.0,
+        "A positive lumped_capacitance is required"
+    );
+    let l = options.surface_width / 2.;
+    let d = zone_depth(options);
+    let (density, specific_heat, _) = LUMPED_MASS_PROPERTIES;
+    let thickness = capacitance / (density * specific_heat * options.surface_width * d);
+    let material = add_normal_material(
+        model,
+        options,
+        "lumped mass",
+        thickness,
+        LUMPED_MASS_PROPERTIES,
+    );
+    let mut construction = Construction::new(prefixed(options, "the lumped mass construction"));
+    construction.materials.push(material);
+    let construction = model.add_construction(construction);
+
+    let the_loop = horizontal_rectangle(
+        options.orientation,
+        (-l, 0.),
+        (l, d),
+        options.surface_height / 2.,
+        true,
+    );
+    let mut surface = Surface::new(
+        prefixed(options, "Internal mass"),
+        Polygon3D::new(the_loop).unwrap(),
+        construction,
+    );
+    surface.set_front_boundary(Boundary::Space(Rc::clone(space)));
+    surface.set_back_boundary(Boundary::Space(Rc::clone(space)));
+    model.add_surface(surface);
+}
+
+/// Adds the surface representing `options.light_shelf_depth` (if any) to `space`
+fn add_light_shelf(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    space: &Rc<Space>,
+    construction: &Rc<Construction>,
+) {
+    let depth = match options.light_shelf_depth {
+        Some(depth) => depth,
+        None => return,
+    };
+    assert!(depth > 0.0, "A positive light_shelf_depth is required");
+    assert!(
+        options.window_width > 0.0 && options.window_height > 0.0,
+        "A light shelf needs a window"
+    );
+    let l = options.window_width / 2.;
+    let head = window_sill(options) + options.window_height;
+    let the_loop = horizontal_rectangle(options.orientation, (-l, 0.), (l, depth), head, true);
+    let mut surface = Surface::new(
+        prefixed(options, "Light shelf"),
+        Polygon3D::new(the_loop).unwrap(),
+        Rc::clone(construction),
+    );
+    surface.set_front_boundary(Boundary::Space(Rc::clone(space)));
+    surface.set_back_boundary(Boundary::Space(Rc::clone(space)));
+    model.add_surface(surface);
+}
+
+/// Adds the surface representing `options.neighbor_obstruction` (if any) to the model
+fn add_neighbor_obstruction(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    construction: &Rc<Construction>,
+) {
+    let (distance, width, height) = match options.neighbor_obstruction {
+        Some(obstruction) => obstruction,
+        None => return,
+    };
+    assert!(
+        distance > 0.0 && width > 0.0 && height > 0.0,
+        "A neighbor_obstruction needs a positive distance, width and height"
+    );
+    let l = width / 2.;
+    // Facing North, towards the main wall
+    let the_loop = vertical_rectangle(
+        options.orientation,
+        (l, -distance),
+        (-l, -distance),
+        0.,
+        height,
+    );
+    model.add_surface(Surface::new(
+        prefixed(options, "Neighbor obstruction"),
+        Polygon3D::new(the_loop).unwrap(),
+        Rc::clone(construction),
+    ));
+}
+
+/// The slope of the `cathedral_ceiling`, in degrees
+pub const CATHEDRAL_CEILING_PITCH: Float = 30.;
+
+/// The height of the ridge of the `cathedral_ceiling` above the top of the walls, in m
+fn cathedral_ceiling_rise(options: &SingleZoneTestBuildingOptions) -> Float {
+    zone_depth(options) / 2. * CATHEDRAL_CEILING_PITCH.to_radians().tan()
+}
+
+/// Adds the two slopes of `options.cathedral_ceiling` (if requested) to `space`
+fn add_cathedral_ceiling(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    space: &Rc<Space>,
+    construction: &Rc<Construction>,
+) {
+    if !options.cathedral_ceiling {
+        return;
+    }
+    let o = options.orientation;
+    let l = options.surface_width / 2.;
+    let h = options.surface_height;
+    let d = zone_depth(options);
+    let rise = cathedral_ceiling_rise(options);
+
+    let (south, north) = gable_slopes(o, l, d, h, rise);
+    for (name, the_loop) in [("Ceiling - South", south), ("Ceiling - North", north)] {
+        let mut surface = Surface::new(
+            prefixed(options, name),
+            Polygon3D::new(the_loop).unwrap(),
+            Rc::clone(construction),
+        );
+        surface.set_back_boundary(Boundary::Space(Rc::clone(space)));
+        model.add_surface(surface);
+    }
+}
+
+/// Adds the surface representing `options.thermal_bridge` (if any) to `space`
+fn add_thermal_bridge(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    space: &Rc<Space>,
+) {
+    let (length, psi) = match options.thermal_bridge {
+        Some(bridge) => bridge,
+        None => return,
+    };
+    assert!(
+        length > 0.0 && psi > 0.0,
+        "A thermal_bridge needs a positive length and psi"
+    );
+    // area / R = (length * width) / (width / psi) = length * psi
+    let construction = add_layers(
+        model,
+        options,
+        "the thermal bridge construction",
+        "Thermal bridge material",
+        &[TestMat::Resistance(THERMAL_BRIDGE_WIDTH / psi)],
+    );
+    let l = length / 2.;
+    let the_loop = vertical_rectangle(
+        options.orientation,
+        (-l, 0.),
+        (l, 0.),
+        -THERMAL_BRIDGE_WIDTH,
+        0.,
+    );
+    let mut surface = Surface::new(
+        prefixed(options, "Thermal bridge"),
+        Polygon3D::new(the_loop).unwrap(),
+        construction,
+    );
+    surface.set_back_boundary(Boundary::Space(Rc::clone(space)));
+    if let Some(boundary) = &options.exterior_boundary {
+        surface.set_front_boundary(boundary.clone());
+    }
+    model.add_surface(surface);
+}
+
+/// Mirrors the surfaces and fenestrations of the model, from `first_surface`
+/// and `first_fenestration` onwards, if `options.flip_handedness`
+pub(crate) fn apply_handedness(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    first_surface: usize,
+    first_fenestration: usize,
+) {
+    if !options.flip_handedness {
+        return;
+    }
+    for s in model.surfaces.iter_mut().skip(first_surface) {
+        let s = Rc::get_mut(s).expect("Surfaces being built are not shared");
+        s.vertices = mirror_polygon(&s.vertices);
+    }
+    for f in model.fenestrations.iter_mut().skip(first_fenestration) {
+        let f = Rc::get_mut(f).expect("Fenestrations being built are not shared");
+        f.vertices = mirror_polygon(&f.vertices);
+    }
+}
+
+/// Appends the zone of [`get_single_zone_test_building`] to an existing `model`
+/// (e.g., to embed it in a larger one).
+///
+/// The names of all the new objects start with `name_prefix` (which
+/// overrides `options.name_prefix`), so several zones can be added to the same model
+/// as long as they use different prefixes. A `prebuilt_construction` is the exception:
+/// it keeps its own name, and it is only added once.
+pub fn add_single_zone_to(
+    model: &mut SimpleModel,
+    header: &mut SimulationStateHeader,
+    options: &SingleZoneTestBuildingOptions,
+    name_prefix: &str,
+) {
+    let options = options.in_si().with_name_prefix(name_prefix.to_string());
+    let options = &options;
+    let (first_surface, first_fenestration) = (model.surfaces.len(), model.fenestrations.len());
+
+    /*************** */
+    /* ADD THE SPACE */
+    /*************** */
+    let mut volume = options.zone_volume;
+    if options.cathedral_ceiling {
+        volume +=
+            options.surface_width * zone_depth(options) * cathedral_ceiling_rise(options) / 2.;
+    }
+    let space = add_space(model, options, "Some space", volume);
+
+    /*********************************** */
+    /* ADD THE MATERIAL AND CONSTRUCTION */
+    /*********************************** */
+    let construction = add_construction(model, options);
+
+    /************************ */
+    /* ADD THE WALL AND WINDOW */
+    /************************ */
+    add_main_wall(model, header, options, &space, &construction, None);
+
+    // The plenum, beneath the space
+    if options.floor_plenum {
+        add_floor_plenum(model, options, &space, &construction);
+    }
+
+    // The floor, if requested
+    add_floor(model, options, &space, &construction);
+
+    // The sloped ceiling, if requested
+    add_cathedral_ceiling(model, options, &space, &construction);
+
+    // The thermal bridge, if any
+    add_thermal_bridge(model, options, &space);
+
+    // The internal mass, if any
+    add_lumped_mass(model, options, &space);
+
+    // The light shelf, if any
+    add_light_shelf(model, options, &space, &construction);
+
+    // The porch, the overhang and the neighbor, if any
+    add_porch(model, options, &construction);
+    add_overhang(model, options, &construction);
+    add_neighbor_obstruction(model, options, &construction);
+
+    // Heater and lights, if needed
+    add_loads(model, options, header, &space);
+
+    apply_handedness(model, options, first_surface, first_fenestration);
+}
+
+/// A single space model with a single surface (optionally) one operable window that has the same construction
+/// as the rest of the walls. Thw front of the surface faces South.
+///
+/// The surface_area includes the window; the window_area is cut down from it.
+pub fn get_single_zone_test_building(
+    options: &SingleZoneTestBuildingOptions,
+) -> (SimpleModel, SimulationStateHeader) {
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+    add_single_zone_to(&mut model, &mut header, options, &options.name_prefix);
     (model, header)
 }
 
+/// Like [`get_single_zone_test_building`], but also returns the given `tags`
+/// (e.g., the name of the test) as a map, so test harnesses can carry
+/// metadata along with the model.
+///
+/// `SimpleModel` has no field for metadata, so the tags are kept beside it.
+/// Later duplicated keys overwrite earlier ones.
+pub fn get_single_zone_test_building_tagged(
+    options: &SingleZoneTestBuildingOptions,
+    tags: &[(&str, &str)],
+) -> (SimpleModel, SimulationStateHeader, HashMap<String, String>) {
+    let (model, header) = get_single_zone_test_building(options);
+    let tags = tags
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    (model, header, tags)
+}
+
 #[cfg(test)]
 mod testing {
 
     use super::*;
+    use simple_model::hvac::HVAC;
+    use simple_model::substance::Substance;
 
     #[test]
     fn test_with_window() {
@@ -397,8 +1940,13 @@ mod testing {
         );
 
         let surf_area = simple_model.surfaces[0].area();
-        let exp_area = surface_width * surface_height - window_height*window_width;
-        assert!( (surf_area - exp_area).abs() < 1e-3 , "area = {}... expecting {}", surf_area, exp_area);
+        let exp_area = surface_width * surface_height - window_height * window_width;
+        assert!(
+            (surf_area - exp_area).abs() < 1e-3,
+            "area = {}... expecting {}",
+            surf_area,
+            exp_area
+        );
     }
 
     #[test]
@@ -424,6 +1972,1155 @@ mod testing {
 
         let surf_area = simple_model.surfaces[0].area();
         let exp_area = surface_width * surface_height;
-        assert!( (surf_area - exp_area).abs() < 1e-3 , "area = {}... expecting {}", surf_area, exp_area);
+        assert!(
+            (surf_area - exp_area).abs() < 1e-3,
+            "area = {}... expecting {}",
+            surf_area,
+            exp_area
+        );
+    }
+
+    #[test]
+    fn test_heater_sign() {
+        // The heater cannot be configured for cooling, so both produce the same heater
+        for heating_power in [1500., -1500.] {
+            let (simple_model, _state_header) =
+                get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                    zone_volume: 40.,
+                    surface_height: 2.,
+                    surface_width: 2.,
+                    heating_power,
+                    construction: vec![TestMat::Concrete(0.2)],
+                    ..Default::default()
+                });
+
+            assert_eq!(simple_model.hvacs.len(), 1);
+            match &*simple_model.hvacs[0] {
+                HVAC::ElectricHeater(heater) => {
+                    let target = heater.target_space().unwrap();
+                    assert!(Rc::ptr_eq(target, &simple_model.spaces[0]));
+                }
+                _ => panic!("Expecting an ElectricHeater"),
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_power_heater() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 2.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+        let (mut simple_model, mut state_header) = get_single_zone_test_building(&options);
+        assert!(simple_model.hvacs.is_empty());
+        add_heater(&mut simple_model, &options, &mut state_header);
+    }
+
+    #[test]
+    fn test_window_divisions() {
+        let window_width = 1.;
+        let window_height = 1.;
+
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                window_height,
+                window_width,
+                window_divisions: (2, 2),
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        assert_eq!(simple_model.fenestrations.len(), 4);
+        let glazed_area: Float = simple_model.fenestrations.iter().map(|f| f.area()).sum();
+        assert!(glazed_area < window_width * window_height);
+
+        // The muntins are part of the wall
+        let surf_area = simple_model.surfaces[0].area();
+        let exp_area = 2. * 2. - glazed_area;
+        assert!(
+            (surf_area - exp_area).abs() < 1e-3,
+            "area = {}... expecting {}",
+            surf_area,
+            exp_area
+        );
+    }
+
+    #[test]
+    fn test_resistance_layer() {
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                construction: vec![TestMat::Resistance(2.5)],
+                ..Default::default()
+            });
+
+        let material = &simple_model.constructions[0].materials[0];
+        let conductivity = match &*material.substance {
+            Substance::Normal(s) => s.thermal_conductivity().unwrap(),
+            _ => panic!("Expecting a Normal substance"),
+        };
+        let r = material.thickness / conductivity;
+        assert!((r - 2.5).abs() < 1e-5, "r = {}... expecting 2.5", r);
+    }
+
+    #[test]
+    fn test_exterior_boundary() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 2.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+
+        // Outdoors by default
+        let (simple_model, _state_header) = get_single_zone_test_building(&options);
+        assert!(simple_model.surfaces[0].front_boundary().is_err());
+        assert!(matches!(
+            simple_model.surfaces[0].back_boundary(),
+            Ok(Boundary::Space(_))
+        ));
+
+        // Overriden
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                exterior_boundary: Some(Boundary::Ground),
+                ..options
+            });
+        assert!(matches!(
+            simple_model.surfaces[0].front_boundary(),
+            Ok(Boundary::Ground)
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_missing_window_construction() {
+        get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 2.,
+            window_height: 1.,
+            window_width: 1.,
+            window_uses_wall_construction: false,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_window_construction() {
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                window_height: 1.,
+                window_width: 1.,
+                window_uses_wall_construction: false,
+                window_construction: vec![TestMat::Glass(0.003, 0.8)],
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        let window_construction = &simple_model.fenestrations[0].construction;
+        assert_eq!(window_construction.name, "the window construction");
+        assert_eq!(window_construction.materials.len(), 1);
+        assert!(!Rc::ptr_eq(
+            window_construction,
+            &simple_model.surfaces[0].construction
+        ));
+    }
+
+    #[test]
+    fn test_with_methods() {
+        let base = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 2.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+
+        for window_width in [0.5, 1.0, 1.5].iter() {
+            let options = base
+                .with_window_width(*window_width)
+                .with_window_height(1.)
+                .with_orientation(90.);
+            assert!((options.window_width - window_width).abs() < 1e-9);
+            // The base is unchanged
+            assert!(base.window_width.abs() < 1e-9);
+
+            let (simple_model, _state_header) = get_single_zone_test_building(&options);
+            let window_area = simple_model.fenestrations[0].area();
+            assert!((window_area - window_width).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_rc_construction() {
+        let u_value = 0.5;
+        let capacitance = 150_000.;
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                wall_overall_u: Some(u_value),
+                wall_areal_capacitance: Some(capacitance),
+                ..Default::default()
+            });
+
+        let construction = &simple_model.surfaces[0].construction;
+        assert_eq!(construction.materials.len(), 3);
+
+        let r = analysis::construction_resistance(construction);
+        assert!((r - 1. / u_value).abs() < 1e-4, "r = {}", r);
+        let c = analysis::construction_areal_capacitance(construction);
+        assert!((c - capacitance).abs() / capacitance < 1e-4, "c = {}", c);
+
+        let ua = total_ua(&simple_model);
+        assert!((ua - 4. * u_value).abs() < 1e-4, "ua = {}", ua);
+    }
+
+    #[test]
+    fn test_prebuilt_construction() {
+        let mut brick = NormalSubstance::new("brick".to_string());
+        brick
+            .set_density(1900.)
+            .set_specific_heat_capacity(840.)
+            .set_thermal_conductivity(0.8);
+        let brick = Rc::new(brick.wrap());
+        let mut construction = Construction::new("brick wall".to_string());
+        construction.materials.push(Rc::new(Material::new(
+            "brick layer".to_string(),
+            Rc::clone(&brick),
+            0.11,
+        )));
+        let construction = Rc::new(construction);
+
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                prebuilt_construction: Some(Rc::clone(&construction)),
+                // This is ignored
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        assert!(Rc::ptr_eq(
+            &simple_model.surfaces[0].construction,
+            &construction
+        ));
+        assert!(simple_model
+            .substances
+            .iter()
+            .any(|s| Rc::ptr_eq(s, &brick)));
+        assert_eq!(simple_model.materials.len(), 1);
+        assert_eq!(simple_model.constructions.len(), 1);
+    }
+
+    #[test]
+    fn test_surface_subdivisions() {
+        let surface_width = 3.;
+        let surface_height = 2.;
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height,
+                surface_width,
+                surface_subdivisions: (2, 3),
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        assert_eq!(simple_model.surfaces.len(), 6);
+        let area: Float = simple_model.surfaces.iter().map(|s| s.area()).sum();
+        let exp_area = surface_width * surface_height;
+        assert!(
+            (area - exp_area).abs() < 1e-3,
+            "area = {}... expecting {}",
+            area,
+            exp_area
+        );
+        for s in simple_model.surfaces.iter() {
+            assert!((s.area() - exp_area / 6.).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_floor_plenum() {
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 4.,
+                floor_plenum: true,
+                heating_power: 100.,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        assert_eq!(simple_model.spaces.len(), 2);
+        let main = &simple_model.spaces[0];
+        let plenum = &simple_model.spaces[1];
+        assert_eq!(plenum.name, "Floor plenum");
+        let exp_volume = 4. * 5. * FLOOR_PLENUM_HEIGHT;
+        assert!((plenum.volume().unwrap() - exp_volume).abs() < 1e-6);
+
+        // Raised floor, between the two spaces
+        let raised = &simple_model.surfaces[1];
+        assert_eq!(raised.name, "Raised floor");
+        assert!((raised.area() - 20.).abs() < 1e-6);
+        assert!(raised.vertices.normal().z < -0.99);
+        match raised.front_boundary().unwrap() {
+            Boundary::Space(s) => assert!(Rc::ptr_eq(s, plenum)),
+            _ => panic!("Expecting the plenum in front of the raised floor"),
+        }
+        match raised.back_boundary().unwrap() {
+            Boundary::Space(s) => assert!(Rc::ptr_eq(s, main)),
+            _ => panic!("Expecting the main space behind the raised floor"),
+        }
+
+        // True floor, on the ground
+        let floor = &simple_model.surfaces[2];
+        assert_eq!(floor.name, "Floor");
+        assert!(matches!(floor.front_boundary(), Ok(Boundary::Ground)));
+        assert!((floor.vertices.outer()[0].z + FLOOR_PLENUM_HEIGHT).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_glazing() {
+        assert_eq!(Glazing::Single.layers().len(), 1);
+        assert_eq!(Glazing::Triple.layers().len(), 5);
+
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                window_height: 1.,
+                window_width: 1.,
+                glazing: Some(Glazing::Double),
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        let window_construction = &simple_model.fenestrations[0].construction;
+        assert_eq!(window_construction.name, "the window construction");
+        let kinds: Vec<bool> = window_construction
+            .materials
+            .iter()
+            .map(|m| matches!(&*m.substance, Substance::Gas(_)))
+            .collect();
+        assert_eq!(kinds, vec![false, true, false]);
+
+        let glass = &window_construction.materials[0];
+        assert!((glass.thickness - GLAZING_PANE_THICKNESS).abs() < 1e-9);
+        match &*glass.substance {
+            Substance::Normal(s) => {
+                assert!(
+                    (s.solar_transmittance().unwrap() - GLAZING_PANE_TRANSMITTANCE).abs() < 1e-9
+                )
+            }
+            _ => panic!("Expecting glass to be a Normal substance"),
+        }
+    }
+
+    #[test]
+    fn test_tagged() {
+        let (model, _header, tags) = get_single_zone_test_building_tagged(
+            &SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            },
+            &[("test", "test_tagged"), ("sha", "abc123")],
+        );
+        assert_eq!(model.surfaces.len(), 1);
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags["test"], "test_tagged");
+        assert_eq!(tags["sha"], "abc123");
+    }
+
+    #[test]
+    fn test_berm_height() {
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 3.,
+                surface_width: 2.,
+                window_height: 1.,
+                window_width: 1.,
+                berm_height: 0.8,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        assert_eq!(simple_model.surfaces.len(), 2);
+        assert_eq!(simple_model.fenestrations.len(), 1);
+
+        let bermed = &simple_model.surfaces[0];
+        assert_eq!(bermed.name, "Surface - bermed");
+        assert!((bermed.area() - 2. * 0.8).abs() < 1e-6);
+        assert!(matches!(bermed.front_boundary(), Ok(Boundary::Ground)));
+
+        let upper = &simple_model.surfaces[1];
+        assert_eq!(upper.name, "Surface");
+        assert!((upper.area() - (2. * 2.2 - 1.)).abs() < 1e-6);
+        assert!(upper.front_boundary().is_err());
+    }
+
+    #[test]
+    fn test_randomize_loads() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 2.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+
+        let a = randomize_loads(&options, 1);
+        let b = randomize_loads(&options, 1);
+        let c = randomize_loads(&options, 2);
+        assert_eq!(a.heating_power, b.heating_power);
+        assert_eq!(a.lighting_power, b.lighting_power);
+        assert!(a.heating_power != c.heating_power || a.lighting_power != c.lighting_power);
+
+        for o in [&a, &c].iter() {
+            assert!(o.heating_power >= 0. && o.heating_power <= 3000.);
+            assert!(o.lighting_power >= 0. && o.lighting_power <= 500.);
+            assert_eq!(o.zone_volume, options.zone_volume);
+            assert_eq!(o.surface_width, options.surface_width);
+        }
+    }
+
+    #[test]
+    fn test_add_single_zone_to() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 2.,
+            window_height: 1.,
+            window_width: 1.,
+            heating_power: 100.,
+            lighting_power: 100.,
+            construction: vec![TestMat::Concrete(0.2), TestMat::Polyurethane(0.02)],
+            ..Default::default()
+        };
+
+        let mut model = SimpleModel::new("Larger model".to_string());
+        let mut header = SimulationStateHeader::new();
+        add_single_zone_to(&mut model, &mut header, &options, "Zone 1 - ");
+        add_single_zone_to(&mut model, &mut header, &options, "Zone 2 - ");
+
+        assert_eq!(model.spaces.len(), 2);
+        assert_eq!(model.spaces[0].name, "Zone 1 - Some space");
+        assert_eq!(model.spaces[1].name, "Zone 2 - Some space");
+        assert_eq!(model.surfaces.len(), 2);
+        assert_eq!(model.fenestrations.len(), 2);
+
+        fn unique<I: Iterator<Item = String>>(names: I) {
+            let names: Vec<String> = names.collect();
+            let mut deduped = names.clone();
+            deduped.sort();
+            deduped.dedup();
+            assert_eq!(names.len(), deduped.len(), "{:?}", names);
+        }
+        unique(model.spaces.iter().map(|s| s.name.clone()));
+        unique(model.surfaces.iter().map(|s| s.name.clone()));
+        unique(model.fenestrations.iter().map(|s| s.name.clone()));
+        unique(model.constructions.iter().map(|s| s.name.clone()));
+        unique(model.materials.iter().map(|s| s.name.clone()));
+        unique(model.substances.iter().map(|s| match &**s {
+            Substance::Normal(s) => s.name.clone(),
+            Substance::Gas(s) => s.name.clone(),
+        }));
+        unique(model.luminaires.iter().map(|s| s.name.clone()));
+
+        // Each zone's loads serve its own space
+        let target = model.luminaires[1].target_space().unwrap();
+        assert!(Rc::ptr_eq(target, &model.spaces[1]));
+    }
+
+    #[test]
+    fn test_split_layers() {
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                split_layers: 4,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        let construction = &simple_model.surfaces[0].construction;
+        assert_eq!(construction.materials.len(), 4);
+        for m in construction.materials.iter() {
+            assert!((m.thickness - 0.05).abs() < 1e-9);
+            assert!(Rc::ptr_eq(
+                &m.substance,
+                &construction.materials[0].substance
+            ));
+        }
+        let r = analysis::construction_resistance(construction);
+        assert!((r - 0.2 / 0.816).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_flip_handedness() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 2.,
+            window_height: 1.,
+            window_width: 1.,
+            flip_handedness: true,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+
+        // The main wall still faces South (i.e., outwards)
+        let (simple_model, _state_header) = get_single_zone_test_building(&options);
+        assert!(simple_model.surfaces[0].vertices.normal().y < -0.99);
+        assert!(simple_model.fenestrations[0].vertices.normal().y < -0.99);
+        let area = simple_model.surfaces[0].area();
+        assert!((area - 3.).abs() < 1e-6, "area = {}", area);
+
+        // With an orientation, the wall is mirrored
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&options.with_orientation(30.));
+        let (plain, _state_header) = get_single_zone_test_building(
+            &options.with_orientation(30.).with_flip_handedness(false),
+        );
+        let n = simple_model.surfaces[0].vertices.normal();
+        let plain_n = plain.surfaces[0].vertices.normal();
+        assert!((n.x + plain_n.x).abs() < 1e-6 && (n.y - plain_n.y).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_face_emissivity() {
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                exterior_emissivity: Some(0.1),
+                interior_emissivity: Some(0.9),
+                construction: vec![
+                    TestMat::Concrete(0.1),
+                    TestMat::Polyurethane(0.05),
+                    TestMat::Concrete(0.1),
+                ],
+                ..Default::default()
+            });
+
+        let emissivities = |m: &Material| match &*m.substance {
+            Substance::Normal(s) => (
+                s.front_thermal_absorbtance().unwrap(),
+                s.back_thermal_absorbtance().unwrap(),
+            ),
+            _ => panic!("Expecting a Normal substance"),
+        };
+        let materials = &simple_model.surfaces[0].construction.materials;
+        assert_eq!(emissivities(&materials[0]), (0.1, 0.84));
+        assert_eq!(emissivities(&materials[1]), (0.84, 0.84));
+        assert_eq!(emissivities(&materials[2]), (0.84, 0.9));
+
+        // Both concrete layers have their own copy, so the shared concrete is not registered
+        assert_eq!(simple_model.substances.len(), 3);
+        for substance in simple_model.substances.iter() {
+            assert!(materials
+                .iter()
+                .any(|m| Rc::ptr_eq(&m.substance, substance)));
+        }
+    }
+
+    #[test]
+    fn test_custom_construction() {
+        let mut brick = NormalSubstance::new("brick".to_string());
+        brick
+            .set_density(1900.)
+            .set_specific_heat_capacity(840.)
+            .set_thermal_conductivity(0.7);
+        let mut wool = NormalSubstance::new("wool".to_string());
+        wool.set_density(20.)
+            .set_specific_heat_capacity(1030.)
+            .set_thermal_conductivity(0.04);
+
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                custom_construction: Some(vec![(brick, 0.1), (wool, 0.05)]),
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        assert_eq!(simple_model.substances.len(), 2);
+        let construction = &simple_model.surfaces[0].construction;
+        assert_eq!(construction.materials.len(), 2);
+        let found: Vec<(String, Float)> = construction
+            .materials
+            .iter()
+            .map(|m| match &*m.substance {
+                Substance::Normal(s) => (s.name.clone(), m.thickness),
+                _ => panic!("Expecting a Normal substance"),
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![("brick".to_string(), 0.1), ("wool".to_string(), 0.05)]
+        );
+        let r = analysis::construction_resistance(construction);
+        assert!((r - (0.1 / 0.7 + 0.05 / 0.04)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_only_used_substances() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 2.,
+            construction: vec![TestMat::Concrete(0.1), TestMat::Concrete(0.1)],
+            ..Default::default()
+        };
+        let (simple_model, _state_header) = get_single_zone_test_building(&options);
+        assert_eq!(simple_model.substances.len(), 1);
+        let materials = &simple_model.surfaces[0].construction.materials;
+        assert!(Rc::ptr_eq(&materials[0].substance, &materials[1].substance));
+
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&options.with_construction(vec![
+                TestMat::Polyurethane(0.02),
+                TestMat::Air(0.05),
+                TestMat::Polyurethane(0.02),
+            ]));
+        assert_eq!(simple_model.substances.len(), 2);
+    }
+
+    #[test]
+    fn test_thermal_bridge() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 4.,
+            construction: vec![TestMat::Concrete(0.2), TestMat::Polyurethane(0.05)],
+            split_layers: 3,
+            ..Default::default()
+        };
+        let (plain, _state_header) = get_single_zone_test_building(&options);
+        let (bridged, _state_header) =
+            get_single_zone_test_building(&options.with_thermal_bridge(Some((4., 0.5))));
+
+        assert_eq!(bridged.surfaces.len(), 2);
+        let bridge = &bridged.surfaces[1];
+        assert_eq!(bridge.name, "Thermal bridge");
+        assert!((bridge.area() - 4. * THERMAL_BRIDGE_WIDTH).abs() < 1e-9);
+
+        let extra = analysis::total_ua(&bridged) - analysis::total_ua(&plain);
+        assert!((extra - 2.).abs() < 1e-6, "extra UA = {}", extra);
+    }
+
+    #[test]
+    fn test_symmetric_windows() {
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 3.,
+                surface_width: 4.,
+                window_height: 1.,
+                window_width: 2.,
+                symmetric_windows: true,
+                orientation: 0.,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        assert_eq!(simple_model.fenestrations.len(), 2);
+        let centroid = |f: &Fenestration| {
+            let l = f.vertices.outer();
+            let n = l.n_vertices() as Float;
+            let x: Float = (0..l.n_vertices()).map(|i| l[i].x).sum();
+            let z: Float = (0..l.n_vertices()).map(|i| l[i].z).sum();
+            (x / n, z / n)
+        };
+        let (left, right) = (
+            &simple_model.fenestrations[0],
+            &simple_model.fenestrations[1],
+        );
+        assert_eq!(left.name, "window left");
+        assert_eq!(right.name, "window right");
+        assert!((left.area() - right.area()).abs() < 1e-9);
+        assert!((left.area() - (2. - MUNTIN_WIDTH) / 2.).abs() < 1e-9);
+
+        let (cl, cr) = (centroid(left), centroid(right));
+        assert!((cl.0 + cr.0).abs() < 1e-9, "{:?} vs {:?}", cl, cr);
+        assert!(cl.0 < 0.);
+        assert!((cl.1 - cr.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_window_shading_coefficient() {
+        let coefficient = 0.6;
+        let (simple_model, _state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                window_height: 1.,
+                window_width: 1.,
+                glazing: Some(Glazing::Double),
+                window_shading_coefficient: Some(coefficient),
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+        let window_construction = &simple_model.fenestrations[0].construction;
+        let transmittances: Vec<Float> = window_construction
+            .materials
+            .iter()
+            .filter_map(|m| match &*m.substance {
+                Substance::Normal(s) => Some(s.solar_transmittance().unwrap()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(transmittances.len(), 2);
+        for tau in transmittances {
+            let exp = GLAZING_PANE_TRANSMITTANCE * coefficient;
+            assert!(
+                (tau - exp).abs() < 1e-6,
+                "tau = {}... expecting {}",
+                tau,
+                exp
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_window_shading_coefficient_out_of_range() {
+        get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 2.,
+            window_height: 1.,
+            window_width: 1.,
+            glazing: Some(Glazing::Single),
+            window_shading_coefficient: Some(1.2),
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_cathedral_ceiling() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 4. * 6. * 3.,
+            surface_width: 4.,
+            surface_height: 3.,
+            cathedral_ceiling: true,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let slopes: Vec<_> = model
+            .surfaces
+            .iter()
+            .filter(|s| s.name.starts_with("Ceiling"))
+            .collect();
+        assert_eq!(slopes.len(), 2);
+        let pitch = CATHEDRAL_CEILING_PITCH.to_radians();
+        for s in slopes.iter() {
+            let n = s.vertices.normal();
+            assert!((n.z - pitch.cos()).abs() < 1e-6, "{}: {:?}", s.name, n);
+            assert!((s.area() - 4. * 3. / pitch.cos()).abs() < 1e-6);
+        }
+        assert!(slopes[0].vertices.normal().y < 0.);
+        assert!(slopes[1].vertices.normal().y > 0.);
+
+        // The walls remain rectangular, so the extra volume is a triangular prism
+        let rise = 3. * pitch.tan();
+        let exp = 4. * 6. * 3. + 4. * 6. * rise / 2.;
+        let found = model.spaces[0].volume().unwrap();
+        assert!(
+            (found - exp).abs() < 1e-6,
+            "volume = {}... expecting {}",
+            found,
+            exp
+        );
+    }
+
+    #[test]
+    fn test_porch() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 2.,
+            porch: Some((1.5, 2.6)),
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let porch: Vec<_> = model
+            .surfaces
+            .iter()
+            .filter(|s| s.name.starts_with("Porch"))
+            .collect();
+        assert_eq!(porch.len(), 3);
+        assert!((porch[0].area() - 1. * 1.5).abs() < 1e-6);
+        assert!(porch[0].vertices.normal().z > 0.99);
+        for s in porch.iter() {
+            assert!(s.front_boundary().is_err() && s.back_boundary().is_err());
+            // They stick out of the wall (at y = 0) and never go through it
+            assert!(s.vertices.normal().y.abs() < 1e-6, "{}", s.name);
+            let the_loop = s.vertices.outer();
+            for i in 0..the_loop.n_vertices() {
+                assert!(the_loop[i].y < 1e-6, "{}: {:?}", s.name, the_loop[i]);
+            }
+        }
+        for fin in &porch[1..] {
+            assert!((fin.area() - 1.5 * 2.6).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_overhang() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            overhang_depth: Some(1.),
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        assert_eq!(model.surfaces.len(), 2);
+        let overhang = &model.surfaces[1];
+        assert_eq!(overhang.name, "Overhang");
+        assert!(overhang.front_boundary().is_err() && overhang.back_boundary().is_err());
+        assert!((overhang.area() - 4. * 1.).abs() < 1e-6);
+        assert!(overhang.vertices.normal().z > 0.99);
+        let the_loop = overhang.vertices.outer();
+        for i in 0..the_loop.n_vertices() {
+            let p = the_loop[i];
+            assert!(
+                (p.z - 3.).abs() < 1e-6 && p.y < 1e-6 && p.y > -1. - 1e-6,
+                "{:?}",
+                p
+            );
+        }
+    }
+
+    #[test]
+    fn test_neighbor_obstruction() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            neighbor_obstruction: Some((10., 20., 8.)),
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        assert_eq!(model.surfaces.len(), 2);
+        let neighbor = &model.surfaces[1];
+        assert_eq!(neighbor.name, "Neighbor obstruction");
+        assert!(neighbor.front_boundary().is_err() && neighbor.back_boundary().is_err());
+        assert!((neighbor.area() - 20. * 8.).abs() < 1e-6);
+        assert!(neighbor.vertices.normal().y > 0.99);
+
+        let the_loop = neighbor.vertices.outer();
+        for i in 0..the_loop.n_vertices() {
+            assert!((the_loop[i].y + 10.).abs() < 1e-6, "{:?}", the_loop[i]);
+        }
+    }
+
+    #[test]
+    fn test_ip_units() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 1000.,
+            surface_width: 10.,
+            surface_height: 10.,
+            heating_power: 1000.,
+            construction: vec![TestMat::Concrete(8.)],
+            units: Units::IP,
+            ..Default::default()
+        });
+
+        let area = model.surfaces[0].area();
+        assert!((area - 9.290304).abs() < 1e-4, "area = {}", area);
+        let volume = model.spaces[0].volume().unwrap();
+        assert!((volume - 28.316847).abs() < 1e-4, "volume = {}", volume);
+        let thickness = model.materials[0].thickness;
+        assert!(
+            (thickness - 0.2032).abs() < 1e-6,
+            "thickness = {}",
+            thickness
+        );
+
+        // Already SI
+        let options = SingleZoneTestBuildingOptions {
+            surface_width: 10.,
+            ..Default::default()
+        };
+        assert!((options.in_si().surface_width - 10.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ip_light_shelf() {
+        let ip = SingleZoneTestBuildingOptions {
+            zone_volume: 1000.,
+            surface_width: 10.,
+            surface_height: 10.,
+            window_width: 4.,
+            window_height: 4.,
+            light_shelf_depth: Some(3.),
+            construction: vec![TestMat::Concrete(8.)],
+            units: Units::IP,
+            ..Default::default()
+        };
+        let si = ip.in_si();
+        assert!((si.light_shelf_depth.unwrap() - 0.9144).abs() < 1e-6);
+
+        let shelf_area = |options: &SingleZoneTestBuildingOptions| {
+            let (model, _header) = get_single_zone_test_building(options);
+            let shelf = model
+                .surfaces
+                .iter()
+                .find(|s| s.name == "Light shelf")
+                .unwrap();
+            shelf.area()
+        };
+        let (found, exp) = (shelf_area(&ip), shelf_area(&si));
+        assert!((found - exp).abs() < 1e-6, "{} vs {}", found, exp);
+        assert!((found - 1.2192 * 0.9144).abs() < 1e-4, "area = {}", found);
+    }
+
+    #[test]
+    fn test_ip_lumped_capacitance() {
+        let ip = SingleZoneTestBuildingOptions {
+            zone_volume: 1000.,
+            surface_width: 10.,
+            surface_height: 10.,
+            lumped_capacitance: Some(100.),
+            construction: vec![TestMat::Concrete(8.)],
+            units: Units::IP,
+            ..Default::default()
+        };
+        let exp = 100. * 1899.1;
+        assert!((ip.in_si().lumped_capacitance.unwrap() - exp).abs() < 1e-6 * exp);
+
+        let (model, _header) = get_single_zone_test_building(&ip);
+        let mass = model
+            .surfaces
+            .iter()
+            .find(|s| s.name == "Internal mass")
+            .unwrap();
+        let (density, specific_heat, _) = LUMPED_MASS_PROPERTIES;
+        let found =
+            density * specific_heat * mass.construction.materials[0].thickness * mass.area();
+        assert!((found - exp).abs() < 1e-4 * exp, "{} vs {}", found, exp);
+    }
+
+    #[test]
+    fn test_reverse_layers() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            construction: vec![TestMat::Concrete(0.2), TestMat::Polyurethane(0.02)],
+            reverse_layers: true,
+            ..Default::default()
+        });
+
+        let layers: Vec<(String, Float)> = model.surfaces[0]
+            .construction
+            .materials
+            .iter()
+            .map(|m| match &*m.substance {
+                Substance::Normal(s) => (s.name.clone(), m.thickness),
+                _ => panic!("Expecting Normal substances"),
+            })
+            .collect();
+        assert_eq!(
+            layers,
+            vec![
+                ("polyurethane".to_string(), 0.02),
+                ("concrete".to_string(), 0.2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_floor_type() {
+        for floor_type in [FloorType::SlabOnGrade, FloorType::SuspendedVented] {
+            let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_width: 4.,
+                surface_height: 2.,
+                floor_type: Some(floor_type),
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            });
+
+            assert_eq!(model.surfaces.len(), 2);
+            let floor = &model.surfaces[1];
+            assert_eq!(floor.name, "Floor");
+            assert!((floor.area() - 20.).abs() < 1e-6);
+            assert!(floor.vertices.normal().z < -0.99);
+            assert!(matches!(floor.back_boundary(), Ok(Boundary::Space(_))));
+            match floor_type {
+                FloorType::SlabOnGrade => {
+                    assert!(matches!(floor.front_boundary(), Ok(Boundary::Ground)))
+                }
+                FloorType::SuspendedVented => assert!(floor.front_boundary().is_err()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_window_head_height() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 2.,
+            window_height: 1.2,
+            window_head_height: Some(2.4),
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let the_loop = model.fenestrations[0].vertices.outer();
+        let zs: Vec<Float> = (0..the_loop.n_vertices()).map(|i| the_loop[i].z).collect();
+        let top = zs.iter().cloned().fold(Float::MIN, Float::max);
+        let bottom = zs.iter().cloned().fold(Float::MAX, Float::min);
+        assert!((top - 2.4).abs() < 1e-6, "top = {}", top);
+        assert!((bottom - 1.2).abs() < 1e-6, "bottom = {}", bottom);
+    }
+
+    #[test]
+    fn test_light_shelf() {
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 2.,
+            window_height: 1.,
+            light_shelf_depth: Some(0.6),
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let shelf = model
+            .surfaces
+            .iter()
+            .find(|s| s.name == "Light shelf")
+            .unwrap();
+        assert!(shelf.vertices.normal().z > 0.99);
+        assert!((shelf.area() - 2. * 0.6).abs() < 1e-6);
+        let the_loop = shelf.vertices.outer();
+        for i in 0..the_loop.n_vertices() {
+            // At the head of the window, and inside the zone
+            assert!((the_loop[i].z - 2.).abs() < 1e-6, "{:?}", the_loop[i]);
+            assert!(the_loop[i].y > -1e-6, "{:?}", the_loop[i]);
+        }
+        assert!(matches!(shelf.front_boundary(), Ok(Boundary::Space(_))));
+        assert!(matches!(shelf.back_boundary(), Ok(Boundary::Space(_))));
+    }
+
+    #[test]
+    fn test_lumped_capacitance() {
+        let capacitance = 2e6;
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 2.,
+            lumped_capacitance: Some(capacitance),
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let mass = model
+            .surfaces
+            .iter()
+            .find(|s| s.name == "Internal mass")
+            .unwrap();
+        assert!(matches!(mass.front_boundary(), Ok(Boundary::Space(_))));
+        assert!(matches!(mass.back_boundary(), Ok(Boundary::Space(_))));
+        assert_eq!(mass.construction.materials.len(), 1);
+        let found = mass.area() * analysis::construction_areal_capacitance(&mass.construction);
+        assert!(
+            (found - capacitance).abs() / capacitance < 1e-4,
+            "found {}... expecting {}",
+            found,
+            capacitance
+        );
+    }
+
+    #[test]
+    fn test_construction_specs() {
+        let spec = |name: &str, thickness, conductivity, density, specific_heat| LayerSpec {
+            name: name.to_string(),
+            thickness,
+            conductivity,
+            density,
+            specific_heat,
+        };
+        let specs = vec![
+            spec("F08 Metal surface", 0.0008, 45.28, 7824., 500.),
+            spec("I02 50mm insulation board", 0.0508, 0.03, 43., 1210.),
+            spec("G01a 19mm gypsum board", 0.019, 0.16, 800., 1090.),
+        ];
+        let (model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 2.,
+            surface_height: 2.,
+            construction_specs: Some(specs.clone()),
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        let construction = &model.surfaces[0].construction;
+        assert_eq!(construction.materials.len(), 3);
+        for (m, spec) in construction.materials.iter().zip(specs.iter()) {
+            assert_eq!(m.name, spec.name);
+            assert!((m.thickness - spec.thickness).abs() < 1e-9);
+            match &*m.substance {
+                Substance::Normal(s) => {
+                    assert_eq!(s.name, spec.name);
+                    assert!((s.thermal_conductivity().unwrap() - spec.conductivity).abs() < 1e-9);
+                    assert!((s.density().unwrap() - spec.density).abs() < 1e-9);
+                    assert!(
+                        (s.specific_heat_capacity().unwrap() - spec.specific_heat).abs() < 1e-9
+                    );
+                    assert!((s.front_thermal_absorbtance().unwrap() - 0.84).abs() < 1e-9);
+                }
+                _ => panic!("Expecting Normal substances"),
+            }
+        }
     }
 }
@@ -0,0 +1,464 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Functions for tweaking models after they have been built
+
+use crate::Float;
+use simple_model::{
+    substance::{Normal, Substance},
+    Boundary, Construction, Material, SimpleModel, Surface,
+};
+use std::rc::Rc;
+
+/// Finds the index of a surface in the model, by name
+pub(crate) fn surface_index(model: &SimpleModel, surface_name: &str) -> Result<usize, String> {
+    model
+        .surfaces
+        .iter()
+        .position(|s| s.name == surface_name)
+        .ok_or_else(|| format!("Could not find surface '{}'", surface_name))
+}
+
+/// Gives the surface called `surface_name` an exterior solar absorbtance of `value`,
+/// leaving the rest of the surfaces unchanged.
+///
+/// Substances are shared between surfaces, so this does not modify them.
+/// Instead, the surface gets its own copy of its construction in which
+/// the exterior (i.e., first) layer is made of a copy of the original
+/// substance with the new front solar absorbtance. These copies are named after the surface.
+/// This fails (leaving the model unchanged) if the surface is referenced outside the model.
+pub fn set_surface_solar_absorbtance(
+    model: &mut SimpleModel,
+    surface_name: &str,
+    value: Float,
+) -> Result<(), String> {
+    let index = surface_index(model, surface_name)?;
+    // Check before changing anything
+    if Rc::get_mut(&mut model.surfaces[index]).is_none() {
+        return Err(format!(
+            "Surface '{}' is referenced elsewhere, so it cannot be modified",
+            surface_name
+        ));
+    }
+    let original = Rc::clone(&model.surfaces[index].construction);
+    let first = original
+        .materials
+        .first()
+        .ok_or_else(|| format!("Surface '{}' has no layers", surface_name))?;
+
+    let mut substance = match &*first.substance {
+        Substance::Normal(s) => s.clone(),
+        _ => {
+            return Err(format!(
+                "The exterior layer of surface '{}' is not a Normal substance",
+                surface_name
+            ))
+        }
+    };
+    substance.name = format!("{} - {}", substance.name, surface_name);
+    substance.set_front_solar_absorbtance(value);
+    let substance = model.add_substance(substance.wrap());
+
+    let material = Material::new(
+        format!("{} - {}", first.name, surface_name),
+        substance,
+        first.thickness,
+    );
+    let material = model.add_material(material);
+
+    let mut construction = (*original).clone();
+    construction.name = format!("{} - {}", original.name, surface_name);
+    construction.materials[0] = material;
+    let construction = model.add_construction(construction);
+
+    Rc::get_mut(&mut model.surfaces[index])
+        .unwrap()
+        .construction = construction;
+    Ok(())
+}
+
+/// Describes the physical properties of a substance (but not its name)
+fn substance_signature(substance: &Substance) -> String {
+    match substance {
+        Substance::Normal(s) => format!(
+            "normal {:?}",
+            [
+                s.density().ok(),
+                s.specific_heat_capacity().ok(),
+                s.thermal_conductivity().ok(),
+                s.front_thermal_absorbtance().ok(),
+                s.back_thermal_absorbtance().ok(),
+                s.front_solar_absorbtance().ok(),
+                s.back_solar_absorbtance().ok(),
+                s.solar_transmittance().ok(),
+            ]
+        ),
+        Substance::Gas(s) => format!("gas {:?}", s.gas().ok()),
+    }
+}
+
+/// Replaces the `Rc`s in `items` that are keys of `replacements` (compared by pointer)
+fn repoint<T>(items: &mut [Rc<T>], replacements: &[(Rc<T>, Rc<T>)]) -> bool {
+    let mut changed = false;
+    for item in items.iter_mut() {
+        if let Some((_, new)) = replacements.iter().find(|(old, _)| Rc::ptr_eq(old, item)) {
+            *item = Rc::clone(new);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Merges the substances that have identical properties (e.g., after adding several zones
+/// to the same model), keeping the first of each group (and its name).
+///
+/// Materials are shared, so the ones that used a merged substance are replaced by copies
+/// that use the kept one, and the same goes for the constructions that used
+/// those materials. The surfaces and fenestrations that used these constructions are
+/// then modified in place, so this fails (leaving the model unchanged) if any of them is
+/// referenced outside the model.
+pub fn dedupe_substances(model: &mut SimpleModel) -> Result<(), String> {
+    let mut kept: Vec<(String, Rc<Substance>)> = Vec::new();
+    let mut substances: Vec<(Rc<Substance>, Rc<Substance>)> = Vec::new();
+    for substance in model.substances.iter() {
+        let signature = substance_signature(substance);
+        match kept.iter().find(|(s, _)| *s == signature) {
+            Some((_, canonical)) => substances.push((Rc::clone(substance), Rc::clone(canonical))),
+            None => kept.push((signature, Rc::clone(substance))),
+        }
+    }
+    if substances.is_empty() {
+        return Ok(());
+    }
+
+    let mut materials: Vec<(Rc<Material>, Rc<Material>)> = Vec::new();
+    for material in model.materials.iter() {
+        if let Some((_, canonical)) = substances
+            .iter()
+            .find(|(old, _)| Rc::ptr_eq(old, &material.substance))
+        {
+            let mut new = (**material).clone();
+            new.substance = Rc::clone(canonical);
+            materials.push((Rc::clone(material), Rc::new(new)));
+        }
+    }
+
+    let mut constructions: Vec<(Rc<Construction>, Rc<Construction>)> = Vec::new();
+    for construction in model.constructions.iter() {
+        let mut new = (**construction).clone();
+        if repoint(&mut new.materials, &materials) {
+            constructions.push((Rc::clone(construction), Rc::new(new)));
+        }
+    }
+
+    // Check before changing anything
+    let affected = |c: &Rc<Construction>| constructions.iter().any(|(old, _)| Rc::ptr_eq(old, c));
+    for surface in model.surfaces.iter_mut() {
+        if affected(&surface.construction) && Rc::get_mut(surface).is_none() {
+            return Err(format!(
+                "Surface '{}' is referenced elsewhere, so it cannot be modified",
+                surface.name
+            ));
+        }
+    }
+    for fenestration in model.fenestrations.iter_mut() {
+        if affected(&fenestration.construction) && Rc::get_mut(fenestration).is_none() {
+            return Err(format!(
+                "Fenestration '{}' is referenced elsewhere, so it cannot be modified",
+                fenestration.name
+            ));
+        }
+    }
+
+    let find = |c: &Rc<Construction>| {
+        constructions
+            .iter()
+            .find(|(old, _)| Rc::ptr_eq(old, c))
+            .map(|(_, new)| Rc::clone(new))
+    };
+    for surface in model.surfaces.iter_mut() {
+        if let Some(new) = find(&surface.construction) {
+            Rc::get_mut(surface).unwrap().construction = new;
+        }
+    }
+    for fenestration in model.fenestrations.iter_mut() {
+        if let Some(new) = find(&fenestration.construction) {
+            Rc::get_mut(fenestration).unwrap().construction = new;
+        }
+    }
+    repoint(&mut model.constructions, &constructions);
+    repoint(&mut model.materials, &materials);
+    model.substances = kept.into_iter().map(|(_, s)| s).collect();
+    Ok(())
+}
+
+/// Common exterior finishes, for [`apply_exterior_finish`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FinishPreset {
+    /// White paint: solar absorbtance of 0.26 and emissivity of 0.9
+    WhitePaint,
+    /// Dark brick: solar absorbtance of 0.8 and emissivity of 0.9
+    DarkBrick,
+    /// Bare aluminum: solar absorbtance of 0.15 and emissivity of 0.1
+    Aluminum,
+}
+
+impl FinishPreset {
+    /// The solar absorbtance and the emissivity (i.e., thermal absorbtance) of the finish
+    pub fn properties(&self) -> (Float, Float) {
+        match self {
+            Self::WhitePaint => (0.26, 0.9),
+            Self::DarkBrick => (0.8, 0.9),
+            Self::Aluminum => (0.15, 0.1),
+        }
+    }
+}
+
+/// Checks whether a surface separates a space (on its back) from the outdoors (on its front)
+fn is_exterior(surface: &Surface) -> bool {
+    surface.front_boundary().is_err() && matches!(surface.back_boundary(), Ok(Boundary::Space(_)))
+}
+
+/// Gives the exterior face of every surface that separates a space from the outdoors (i.e.,
+/// whose back is a space and whose front boundary is not set) the solar absorbtance and the
+/// emissivity of `finish`.
+///
+/// As in [`set_surface_solar_absorbtance`], the shared substances are not modified. Each
+/// construction used by an exterior surface gets a copy (named after the finish) whose first
+/// layer is made of a finished copy of the original substance. Fenestrations and
+/// free-standing shading surfaces (e.g., a porch or an overhang) are left unchanged.
+/// This fails (leaving the model unchanged) if the exterior layer of
+/// one of these surfaces is not a Normal substance, or if one of them is referenced outside
+/// the model.
+pub fn apply_exterior_finish(model: &mut SimpleModel, finish: FinishPreset) -> Result<(), String> {
+    let (solar, thermal) = finish.properties();
+    let finish_name = format!("{:?}", finish);
+
+    // Check (and prepare the finished substances) before changing anything
+    let mut finished: Vec<(Rc<Construction>, Normal)> = Vec::new();
+    for surface in model.surfaces.iter().filter(|s| is_exterior(s)) {
+        if Rc::strong_count(surface) > 1 {
+            return Err(format!(
+                "Surface '{}' is referenced elsewhere, so it cannot be modified",
+                surface.name
+            ));
+        }
+        let original = &surface.construction;
+        if finished.iter().any(|(old, _)| Rc::ptr_eq(old, original)) {
+            continue;
+        }
+        let first = original
+            .materials
+            .first()
+            .ok_or_else(|| format!("Surface '{}' has no layers", surface.name))?;
+        let mut substance = match &*first.substance {
+            Substance::Normal(s) => s.clone(),
+            _ => {
+                return Err(format!(
+                    "The exterior layer of surface '{}' is not a Normal substance",
+                    surface.name
+                ))
+            }
+        };
+        substance.name = format!("{} - {}", substance.name, finish_name);
+        substance.set_front_solar_absorbtance(solar);
+        substance.set_front_thermal_absorbtance(thermal);
+        finished.push((Rc::clone(original), substance));
+    }
+
+    let mut constructions: Vec<(Rc<Construction>, Rc<Construction>)> = Vec::new();
+    for (original, substance) in finished {
+        let substance = model.add_substance(substance.wrap());
+        let first = &original.materials[0];
+        let material = Material::new(
+            format!("{} - {}", first.name, finish_name),
+            substance,
+            first.thickness,
+        );
+        let material = model.add_material(material);
+
+        let mut construction = (*original).clone();
+        construction.name = format!("{} - {}", original.name, finish_name);
+        construction.materials[0] = material;
+        let construction = model.add_construction(construction);
+        constructions.push((original, construction));
+    }
+
+    for surface in model.surfaces.iter_mut().filter(|s| is_exterior(s)) {
+        if let Some((_, new)) = constructions
+            .iter()
+            .find(|(old, _)| Rc::ptr_eq(old, &surface.construction))
+        {
+            Rc::get_mut(surface).unwrap().construction = Rc::clone(new);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use crate::{
+        add_single_zone_to, get_courtyard_building, get_single_zone_test_building,
+        SingleZoneTestBuildingOptions, TestMat,
+    };
+    use simple_model::SimulationStateHeader;
+
+    /// Gets the front solar absorbtance of the exterior layer of a surface
+    fn exterior_absorbtance(model: &SimpleModel, surface_name: &str) -> Float {
+        let i = surface_index(model, surface_name).unwrap();
+        match &*model.surfaces[i].construction.materials[0].substance {
+            Substance::Normal(s) => s.front_solar_absorbtance().unwrap(),
+            _ => panic!("Expecting a Normal substance"),
+        }
+    }
+
+    #[test]
+    fn test_set_surface_solar_absorbtance() {
+        let (mut model, _header) = get_courtyard_building(
+            &SingleZoneTestBuildingOptions {
+                surface_width: 10.,
+                surface_height: 3.,
+                solar_absorbtance: 0.7,
+                construction: vec![TestMat::Concrete(0.2), TestMat::Polyurethane(0.02)],
+                ..Default::default()
+            },
+            0.5,
+        );
+
+        set_surface_solar_absorbtance(&mut model, "Outer wall South", 0.95).unwrap();
+
+        for s in model.surfaces.iter() {
+            let exp = if s.name == "Outer wall South" {
+                0.95
+            } else {
+                0.7
+            };
+            let found = exterior_absorbtance(&model, &s.name);
+            assert!((found - exp).abs() < 1e-6, "{}: {}", s.name, found);
+            assert_eq!(s.construction.materials.len(), 2);
+        }
+
+        assert!(set_surface_solar_absorbtance(&mut model, "Nope", 0.95).is_err());
+
+        // A shared surface is left alone, and nothing is added to the model
+        let i = surface_index(&model, "Outer wall North").unwrap();
+        let _shared = Rc::clone(&model.surfaces[i]);
+        let counts =
+            |m: &SimpleModel| (m.substances.len(), m.materials.len(), m.constructions.len());
+        let before = counts(&model);
+        assert!(set_surface_solar_absorbtance(&mut model, "Outer wall North", 0.95).is_err());
+        assert_eq!(counts(&model), before);
+        assert!((exterior_absorbtance(&model, "Outer wall North") - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_dedupe_substances() {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        };
+        let mut model = SimpleModel::new("The SimpleModel".to_string());
+        let mut header = SimulationStateHeader::new();
+        add_single_zone_to(&mut model, &mut header, &options, "Zone 1 - ");
+        add_single_zone_to(&mut model, &mut header, &options, "Zone 2 - ");
+        assert_eq!(model.substances.len(), 2);
+
+        dedupe_substances(&mut model).unwrap();
+        assert_eq!(model.substances.len(), 1);
+        let concrete = &model.substances[0];
+        for m in model.materials.iter() {
+            assert!(Rc::ptr_eq(&m.substance, concrete));
+        }
+        let layers = model
+            .surfaces
+            .iter()
+            .map(|s| &s.construction)
+            .chain(model.fenestrations.iter().map(|f| &f.construction))
+            .flat_map(|c| c.materials.iter());
+        for m in layers {
+            assert!(Rc::ptr_eq(&m.substance, concrete), "{}", m.name);
+        }
+        // The names of the zones are unchanged
+        assert_eq!(model.surfaces[1].name, "Zone 2 - Surface");
+    }
+
+    #[test]
+    fn test_apply_exterior_finish() {
+        let (mut model, _header) = get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            solar_absorbtance: 0.7,
+            construction: vec![TestMat::Concrete(0.2), TestMat::Polyurethane(0.02)],
+            overhang_depth: Some(1.),
+            ..Default::default()
+        });
+        let n_surfaces = model.surfaces.len();
+
+        apply_exterior_finish(&mut model, FinishPreset::WhitePaint).unwrap();
+
+        let found = exterior_absorbtance(&model, "Surface");
+        assert!((found - 0.26).abs() < 1e-6, "{}", found);
+        match &*model.surfaces[0].construction.materials[0].substance {
+            Substance::Normal(s) => {
+                assert!((s.front_thermal_absorbtance().unwrap() - 0.9).abs() < 1e-6)
+            }
+            _ => panic!("Expecting a Normal substance"),
+        }
+        // The original substances are untouched, and the window is not painted
+        match &*model.fenestrations[0].construction.materials[0].substance {
+            Substance::Normal(s) => {
+                assert!((s.front_solar_absorbtance().unwrap() - 0.26).abs() > 1e-3)
+            }
+            _ => panic!("Expecting a Normal substance"),
+        }
+        assert!(model
+            .substances
+            .iter()
+            .any(|s| matches!(&**s, Substance::Normal(n) if (n.front_solar_absorbtance().unwrap() - 0.7).abs() < 1e-6)));
+
+        // The overhang is a shading surface, so it keeps its finish
+        let found = exterior_absorbtance(&model, "Overhang");
+        assert!((found - 0.7).abs() < 1e-6, "{}", found);
+
+        // The finished copies are registered in the model
+        assert_eq!(model.surfaces.len(), n_surfaces);
+        let construction = &model.surfaces[0].construction;
+        assert!(model
+            .constructions
+            .iter()
+            .any(|c| Rc::ptr_eq(c, construction)));
+        let material = &construction.materials[0];
+        assert!(model.materials.iter().any(|m| Rc::ptr_eq(m, material)));
+        assert!(model
+            .substances
+            .iter()
+            .any(|s| Rc::ptr_eq(s, &material.substance)));
+    }
+}
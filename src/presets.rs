@@ -0,0 +1,2008 @@
+/*
+MIT License
+Copyright (c) 2021 Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Test models that go beyond the single wall of
+//! [`get_single_zone_test_building`](crate::get_single_zone_test_building)
+
+use crate::geometry::{gable_slopes, horizontal_rectangle, local_loop, vertical_rectangle};
+use crate::{
+    add_construction, add_loads, add_main_wall, add_space, add_window_construction,
+    apply_handedness, prefixed, Float, SingleZoneTestBuildingOptions, TestMat,
+};
+use geometry3d::{Loop3D, Polygon3D};
+use simple_model::{
+    substance::Normal, Boundary, Construction, Fenestration, FenestrationPositions,
+    FenestrationType, SimpleModel, SimulationStateHeader, Space, Surface,
+};
+use std::rc::Rc;
+
+/// Adds a [`Surface`] with the given boundaries to the model
+pub(crate) fn add_surface(
+    model: &mut SimpleModel,
+    name: &str,
+    polygon: Polygon3D,
+    construction: &Rc<Construction>,
+    front: Option<Boundary>,
+    back: Option<Boundary>,
+) {
+    let mut surface = Surface::new(name.to_string(), polygon, Rc::clone(construction));
+    if let Some(front) = front {
+        surface.set_front_boundary(front);
+    }
+    if let Some(back) = back {
+        surface.set_back_boundary(back);
+    }
+    model.add_surface(surface);
+}
+
+/// Adds a window (i.e., a [`Fenestration`]) with the given boundaries to the model
+pub(crate) fn add_window(
+    model: &mut SimpleModel,
+    header: &mut SimulationStateHeader,
+    name: &str,
+    polygon: Polygon3D,
+    construction: &Rc<Construction>,
+    front: Option<Boundary>,
+    back: Option<Boundary>,
+) {
+    let mut fenestration = Fenestration::new(
+        name.to_string(),
+        polygon,
+        Rc::clone(construction),
+        FenestrationPositions::Binary,
+        FenestrationType::Window,
+    );
+    if let Some(front) = front {
+        fenestration.set_front_boundary(front);
+    }
+    if let Some(back) = back {
+        fenestration.set_back_boundary(back);
+    }
+    model.add_fenestration(fenestration, header);
+}
+
+/// A single space shaped as a ring around a square courtyard.
+///
+/// The footprint is a square of side `options.surface_width`, and the
+/// courtyard is a centred square of side `courtyard_ratio * options.surface_width`.
+/// All walls are `options.surface_height` tall and use `options.construction`.
+///
+/// The model has four outer walls (facing away from the building), four
+/// courtyard walls (facing the courtyard) and a floor shaped as an annulus
+/// (i.e., a polygon with a hole) that is in contact with the ground.
+///
+/// The `zone_volume` and the window options are ignored: the volume is
+/// calculated from the geometry.
+pub fn get_courtyard_building(
+    options: &SingleZoneTestBuildingOptions,
+    courtyard_ratio: Float,
+) -> (SimpleModel, SimulationStateHeader) {
+    assert!(
+        options.surface_width > 0.0 && options.surface_height > 0.0,
+        "A positive surface_area option is needed (Float)"
+    );
+    assert!(
+        courtyard_ratio > 0.0 && courtyard_ratio < 1.0,
+        "courtyard_ratio needs to be between 0 and 1... found {}",
+        courtyard_ratio
+    );
+
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let o = options.orientation;
+    let w = options.surface_width;
+    let h = options.surface_height;
+    let c = courtyard_ratio * w;
+
+    let volume = (w * w - c * c) * h;
+    let space = add_space(&mut model, options, "Some space", volume);
+    let construction = add_construction(&mut model, options);
+
+    // Outer ring... counterclockwise, so that walls face out
+    let (xw, xe) = (-w / 2., w / 2.);
+    let (ys, yn) = (0., w);
+    let outer = [
+        ("Outer wall South", (xw, ys), (xe, ys)),
+        ("Outer wall East", (xe, ys), (xe, yn)),
+        ("Outer wall North", (xe, yn), (xw, yn)),
+        ("Outer wall West", (xw, yn), (xw, ys)),
+    ];
+
+    // Courtyard ring... clockwise, so that walls face the courtyard
+    let (cw, ce) = (-c / 2., c / 2.);
+    let (cs, cn) = (w / 2. - c / 2., w / 2. + c / 2.);
+    let inner = [
+        ("Courtyard wall South", (ce, cs), (cw, cs)),
+        ("Courtyard wall West", (cw, cs), (cw, cn)),
+        ("Courtyard wall North", (cw, cn), (ce, cn)),
+        ("Courtyard wall East", (ce, cn), (ce, cs)),
+    ];
+
+    for (name, start, end) in outer.iter().chain(inner.iter()) {
+        let p = Polygon3D::new(vertical_rectangle(o, *start, *end, 0., h)).unwrap();
+        add_surface(
+            &mut model,
+            &prefixed(options, name),
+            p,
+            &construction,
+            None,
+            Some(Boundary::Space(Rc::clone(&space))),
+        );
+    }
+
+    // Floor... an annulus facing down.
+    let outer_loop: Loop3D = horizontal_rectangle(o, (xw, ys), (xe, yn), 0., false);
+    let courtyard_loop: Loop3D = horizontal_rectangle(o, (cw, cs), (ce, cn), 0., false);
+    let mut floor = Polygon3D::new(outer_loop).unwrap();
+    floor.cut_hole(courtyard_loop).unwrap();
+    add_surface(
+        &mut model,
+        &prefixed(options, "Floor"),
+        floor,
+        &construction,
+        Some(Boundary::Ground),
+        Some(Boundary::Space(Rc::clone(&space))),
+    );
+
+    add_loads(&mut model, options, &mut header, &space);
+
+    apply_handedness(&mut model, options, 0, 0);
+
+    (model, header)
+}
+
+/// The depth of the zones (i.e., `zone_volume / (surface_width * surface_height)`),
+/// for presets that need the zone described by the options to have a floor plan.
+pub(crate) fn zone_depth(options: &SingleZoneTestBuildingOptions) -> Float {
+    assert!(
+        options.surface_width > 0.0 && options.surface_height > 0.0,
+        "A positive surface_area option is needed (Float)"
+    );
+    options.zone_volume / (options.surface_width * options.surface_height)
+}
+
+/// The height of the plenum added by the `floor_plenum` option, in m
+pub const FLOOR_PLENUM_HEIGHT: Float = 0.3;
+
+/// Adds a plenum of [`FLOOR_PLENUM_HEIGHT`] beneath `space`, spanning the
+/// footprint of the zone (i.e., `surface_width` by [`zone_depth`]).
+///
+/// The "Raised floor" faces down into the "Floor plenum", and the plenum's
+/// "Floor" faces the ground. Both use `construction`. The plenum has no walls.
+pub(crate) fn add_floor_plenum(
+    model: &mut SimpleModel,
+    options: &SingleZoneTestBuildingOptions,
+    space: &Rc<Space>,
+    construction: &Rc<Construction>,
+) {
+    let o = options.orientation;
+    let l = options.surface_width / 2.;
+    let d = zone_depth(options);
+    let volume = options.surface_width * d * FLOOR_PLENUM_HEIGHT;
+    let plenum = add_space(model, options, "Floor plenum", volume);
+
+    let p = Polygon3D::new(horizontal_rectangle(o, (-l, 0.), (l, d), 0., false)).unwrap();
+    add_surface(
+        model,
+        &prefixed(options, "Raised floor"),
+        p,
+        construction,
+        Some(Boundary::Space(Rc::clone(&plenum))),
+        Some(Boundary::Space(Rc::clone(space))),
+    );
+
+    let p = Polygon3D::new(horizontal_rectangle(
+        o,
+        (-l, 0.),
+        (l, d),
+        -FLOOR_PLENUM_HEIGHT,
+        false,
+    ))
+    .unwrap();
+    add_surface(
+        model,
+        &prefixed(options, "Floor"),
+        p,
+        construction,
+        Some(Boundary::Ground),
+        Some(Boundary::Space(plenum)),
+    );
+}
+
+/// A heated main space with an unconditioned buffer space (e.g., a garage
+/// or an attic) attached to its North.
+///
+/// The main space gets the wall and window of [`get_single_zone_test_building`](crate::get_single_zone_test_building).
+/// The buffer space has the same volume and an exterior wall of the same
+/// size (with no window) facing North. Both spaces share an
+/// opaque partition, parallel to the exterior walls, with the same construction.
+///
+/// The heater and the luminaire (if requested) are only added to the main
+/// space, so the temperature of the buffer floats.
+pub fn get_building_with_buffer(
+    options: &SingleZoneTestBuildingOptions,
+) -> (SimpleModel, SimulationStateHeader) {
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let main = add_space(&mut model, options, "Some space", options.zone_volume);
+    let buffer = add_space(&mut model, options, "Buffer space", options.zone_volume);
+    let construction = add_construction(&mut model, options);
+
+    add_main_wall(&mut model, &mut header, options, &main, &construction, None);
+
+    let o = options.orientation;
+    let l = options.surface_width / 2.;
+    let h = options.surface_height;
+    let d = zone_depth(options);
+
+    // Partition, facing the buffer
+    let p = Polygon3D::new(vertical_rectangle(o, (l, d), (-l, d), 0., h)).unwrap();
+    add_surface(
+        &mut model,
+        &prefixed(options, "Partition"),
+        p,
+        &construction,
+        Some(Boundary::Space(Rc::clone(&buffer))),
+        Some(Boundary::Space(Rc::clone(&main))),
+    );
+
+    // Exterior wall of the buffer, facing North
+    let p = Polygon3D::new(vertical_rectangle(o, (l, 2. * d), (-l, 2. * d), 0., h)).unwrap();
+    add_surface(
+        &mut model,
+        &prefixed(options, "Buffer wall"),
+        p,
+        &construction,
+        None,
+        Some(Boundary::Space(Rc::clone(&buffer))),
+    );
+
+    add_loads(&mut model, options, &mut header, &main);
+
+    apply_handedness(&mut model, options, 0, 0);
+
+    (model, header)
+}
+
+/// The options that produce the [`cie_reference_room`]
+fn cie_reference_room_options() -> SingleZoneTestBuildingOptions {
+    SingleZoneTestBuildingOptions {
+        zone_volume: 3.5 * 9. * 3.,
+        surface_width: 3.5,
+        surface_height: 3.,
+        window_width: 3.3,
+        window_height: 2.8,
+        construction: vec![TestMat::Concrete(0.2)],
+        ..Default::default()
+    }
+}
+
+/// The standard reference room used in daylighting research (e.g., CIE
+/// test cases): a room of 3.5 m (wide) by 9 m (deep) by 3 m (high).
+///
+/// Only the short South wall (3.5 m × 3 m) is modelled, and its
+/// window (3.3 m × 2.8 m) covers most of it. The wall is made of 20 cm of concrete
+/// and the window shares that construction, as in [`get_single_zone_test_building`](crate::get_single_zone_test_building).
+pub fn cie_reference_room() -> (SimpleModel, SimulationStateHeader) {
+    crate::get_single_zone_test_building(&cie_reference_room_options())
+}
+
+/// A function that gives the options of a case
+type CaseOptions = fn() -> SingleZoneTestBuildingOptions;
+
+/// The validation cases that can be requested by name: through [`get_named_case`],
+/// [`effective_options`] and (the BESTEST ones, which are named after their numbers)
+/// [`bestest_suite`]
+const NAMED_CASES: &[(&str, CaseOptions)] = &[
+    ("cie_reference_room", cie_reference_room_options),
+    ("600", || bestest_options(false, false, false)),
+    ("600FF", || bestest_options(false, true, false)),
+    ("610", || bestest_options(false, false, true)),
+    ("900", || bestest_options(true, false, false)),
+    ("900FF", || bestest_options(true, true, false)),
+    ("910", || bestest_options(true, false, true)),
+];
+
+/// Finds the options of a case in [`NAMED_CASES`], or lists the available cases
+fn named_case_options(name: &str) -> Result<CaseOptions, String> {
+    match NAMED_CASES.iter().find(|(n, _)| *n == name) {
+        Some((_, options)) => Ok(*options),
+        None => {
+            let names: Vec<&str> = NAMED_CASES.iter().map(|(n, _)| *n).collect();
+            Err(format!(
+                "Unknown case '{}'... available cases are: {}",
+                name,
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// Builds a published validation case by name (e.g., `"cie_reference_room"`, or
+/// one of the cases of [`bestest_suite`], such as `"600"`), which is convenient for data-driven test runners.
+///
+/// Unknown names produce an error that lists the available cases.
+pub fn get_named_case(name: &str) -> Result<(SimpleModel, SimulationStateHeader), String> {
+    let options = named_case_options(name)?;
+    Ok(crate::get_single_zone_test_building(&options()))
+}
+
+/// The [`SingleZoneTestBuildingOptions`] that produce a case of [`get_named_case`], so it can be tweaked
+/// and rebuilt with [`get_single_zone_test_building`](crate::get_single_zone_test_building).
+///
+/// Unknown names produce an error that lists the available cases.
+pub fn effective_options(case: &str) -> Result<SingleZoneTestBuildingOptions, String> {
+    Ok(named_case_options(case)?())
+}
+
+/// The power of the heater of the BESTEST cases of [`bestest_suite`] that are not
+/// free-floating, in W
+///
+/// This value is not part of the standard, which uses an ideal thermostat with
+/// unlimited heating and cooling capacity. It is this crate's stand-in, because the
+/// heaters of `simple_model` have a fixed maximum power and no cooling.
+pub const BESTEST_HEATING_POWER: Float = 3000.;
+
+/// A BESTEST substance, with the emissivity (0.9) and solar absorbtance (0.6) of the
+/// surfaces of the test cases
+fn bestest_substance(
+    name: &str,
+    conductivity: Float,
+    density: Float,
+    specific_heat: Float,
+) -> Normal {
+    let mut substance = Normal::new(name.to_string());
+    substance
+        .set_thermal_conductivity(conductivity)
+        .set_density(density)
+        .set_specific_heat_capacity(specific_heat)
+        .set_front_thermal_absorbtance(0.9)
+        .set_back_thermal_absorbtance(0.9)
+        .set_front_solar_absorbtance(0.6)
+        .set_back_solar_absorbtance(0.6);
+    substance
+}
+
+/// The options of a BESTEST case with a lightweight (600 series) or heavyweight
+/// (900 series) wall, with or without a heater, and with or without the 1 m
+/// overhang of cases 610 and 910
+fn bestest_options(
+    heavyweight: bool,
+    free_float: bool,
+    overhang: bool,
+) -> SingleZoneTestBuildingOptions {
+    // From the outside in
+    let siding = (bestest_substance("wood siding", 0.14, 530., 900.), 0.009);
+    let wall = if heavyweight {
+        vec![
+            siding,
+            (
+                bestest_substance("foam insulation", 0.04, 10., 1400.),
+                0.0615,
+            ),
+            (bestest_substance("concrete block", 0.51, 1400., 1000.), 0.1),
+        ]
+    } else {
+        vec![
+            siding,
+            (
+                bestest_substance("fiberglass quilt", 0.04, 12., 840.),
+                0.066,
+            ),
+            (bestest_substance("plasterboard", 0.16, 950., 840.), 0.012),
+        ]
+    };
+    let volume = 8. * 6. * 2.7;
+    SingleZoneTestBuildingOptions {
+        zone_volume: volume,
+        surface_width: 8.,
+        surface_height: 2.7,
+        custom_construction: Some(wall),
+        window_width: 6. + crate::MUNTIN_WIDTH,
+        window_height: 2.,
+        symmetric_windows: true,
+        glazing: Some(crate::Glazing::Double),
+        infiltration_rate: 0.5 * volume / 3600.,
+        lighting_power: 200.,
+        heating_power: if free_float {
+            0.
+        } else {
+            BESTEST_HEATING_POWER
+        },
+        emmisivity: 0.9,
+        solar_absorbtance: 0.6,
+        overhang_depth: if overhang { Some(1.) } else { None },
+        ..Default::default()
+    }
+}
+
+/// The BESTEST (ANSI/ASHRAE Standard 140) cases that this crate can represent, keyed
+/// by their standard names: `"600"` and `"900"` (lightweight and heavyweight, with a heater
+/// of [`BESTEST_HEATING_POWER`] instead of the ideal thermostat of the standard), their free-floating versions `"600FF"` and `"900FF"`, and
+/// `"610"` and `"910"`, which add a 1 m deep overhang along the top of the South wall
+/// (see `overhang_depth` in [`SingleZoneTestBuildingOptions`]).
+///
+/// As in [`cie_reference_room`], only the South wall of the 8 m × 6 m × 2.7 m zone is
+/// modelled, with two 3 m × 2 m double-glazed windows (see `symmetric_windows` in
+/// [`SingleZoneTestBuildingOptions`]), 0.5 air changes per hour of infiltration and
+/// 200 W of internal gains (as a luminaire). The layers of the walls follow the standard.
+/// Cases 620, 630, 920 and 930 move the windows to the East and West walls, which these
+/// single-wall models do not have, and `simple_model` has no thermostats or schedules
+/// for the setback and night ventilation of cases 640, 650, 940 and 950, so they are not included.
+/// Because the heater replaces the unlimited heating and cooling of the standard, the
+/// loads of the conditioned cases are not comparable with the published results.
+pub fn bestest_suite() -> Vec<(String, SimpleModel, SimulationStateHeader)> {
+    NAMED_CASES
+        .iter()
+        .filter(|(name, _)| name.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|(name, options)| {
+            let (model, header) = crate::get_single_zone_test_building(&options());
+            (name.to_string(), model, header)
+        })
+        .collect()
+}
+
+/// Two spaces of different volumes (`volume_a` and `volume_b`), side by side.
+///
+/// Space A gets the wall and window of [`get_single_zone_test_building`](crate::get_single_zone_test_building).
+/// Space B is to its East, with an opaque exterior wall of the same size
+/// (`surface_width` by `surface_height`) facing the same direction.
+/// The spaces share a partition perpendicular to the exterior walls, as deep
+/// as the shallower space (i.e., `volume / (surface_width * surface_height)`).
+///
+/// The heater and the luminaire (if requested) are only added to space A.
+/// The `zone_volume` option is ignored.
+pub fn get_two_zone_custom(
+    volume_a: Float,
+    volume_b: Float,
+    options: &SingleZoneTestBuildingOptions,
+) -> (SimpleModel, SimulationStateHeader) {
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let space_a = add_space(&mut model, options, "Space A", volume_a);
+    let space_b = add_space(&mut model, options, "Space B", volume_b);
+    let construction = add_construction(&mut model, options);
+
+    add_main_wall(
+        &mut model,
+        &mut header,
+        options,
+        &space_a,
+        &construction,
+        None,
+    );
+
+    let o = options.orientation;
+    let w = options.surface_width;
+    let h = options.surface_height;
+
+    // Exterior wall of B
+    let p = Polygon3D::new(vertical_rectangle(
+        o,
+        (w / 2., 0.),
+        (3. * w / 2., 0.),
+        0.,
+        h,
+    ))
+    .unwrap();
+    add_surface(
+        &mut model,
+        &prefixed(options, "Surface B"),
+        p,
+        &construction,
+        None,
+        Some(Boundary::Space(Rc::clone(&space_b))),
+    );
+
+    // Partition, facing B
+    let depth = volume_a.min(volume_b) / (w * h);
+    let p = Polygon3D::new(vertical_rectangle(o, (w / 2., 0.), (w / 2., depth), 0., h)).unwrap();
+    add_surface(
+        &mut model,
+        &prefixed(options, "Partition"),
+        p,
+        &construction,
+        Some(Boundary::Space(Rc::clone(&space_b))),
+        Some(Boundary::Space(Rc::clone(&space_a))),
+    );
+
+    add_loads(&mut model, options, &mut header, &space_a);
+
+    apply_handedness(&mut model, options, 0, 0);
+
+    (model, header)
+}
+
+/// The fraction of the width and height of the South wall of
+/// [`get_atrium_building`] that is glazed
+const ATRIUM_WINDOW_FRACTION: Float = 0.9;
+
+/// The fraction of the width and depth of the roof of
+/// [`get_atrium_building`] that is covered by the skylight
+const ATRIUM_SKYLIGHT_FRACTION: Float = 0.5;
+
+/// A tall, heavily glazed atrium.
+///
+/// It has the footprint of the zone described by the `options` (i.e.,
+/// `surface_width` wide and `zone_volume / (surface_width * surface_height)`
+/// deep) but it is `height` tall, so its volume is `footprint * height`.
+/// The South wall is glazed over 90% of its width and height (i.e., a 0.81
+/// window-to-wall ratio), and a flat roof has a centred skylight covering 50% of its width and depth.
+///
+/// The window options are ignored, but the glazing follows the same construction
+/// rules as the window of [`get_single_zone_test_building`](crate::get_single_zone_test_building).
+pub fn get_atrium_building(
+    options: &SingleZoneTestBuildingOptions,
+    height: Float,
+) -> (SimpleModel, SimulationStateHeader) {
+    assert!(height > 0.0, "A positive atrium height is required");
+    let depth = zone_depth(options);
+    let w = options.surface_width;
+    let options = SingleZoneTestBuildingOptions {
+        zone_volume: w * depth * height,
+        surface_height: height,
+        window_width: ATRIUM_WINDOW_FRACTION * w,
+        window_height: ATRIUM_WINDOW_FRACTION * height,
+        window_divisions: (1, 1),
+        ..options.clone()
+    };
+
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let space = add_space(&mut model, &options, "Some space", options.zone_volume);
+    let construction = add_construction(&mut model, &options);
+    let glazing = add_window_construction(&mut model, &options, &construction);
+
+    add_main_wall(
+        &mut model,
+        &mut header,
+        &options,
+        &space,
+        &construction,
+        Some(&glazing),
+    );
+
+    // Roof, with a skylight
+    let o = options.orientation;
+    let mut roof = Polygon3D::new(horizontal_rectangle(
+        o,
+        (-w / 2., 0.),
+        (w / 2., depth),
+        height,
+        true,
+    ))
+    .unwrap();
+    let (sw, sd) = (
+        ATRIUM_SKYLIGHT_FRACTION * w,
+        ATRIUM_SKYLIGHT_FRACTION * depth,
+    );
+    let skylight = horizontal_rectangle(
+        o,
+        (-sw / 2., depth / 2. - sd / 2.),
+        (sw / 2., depth / 2. + sd / 2.),
+        height,
+        true,
+    );
+    roof.cut_hole(skylight.clone()).unwrap();
+    add_surface(
+        &mut model,
+        &prefixed(&options, "Roof"),
+        roof,
+        &construction,
+        None,
+        Some(Boundary::Space(Rc::clone(&space))),
+    );
+    add_window(
+        &mut model,
+        &mut header,
+        &prefixed(&options, "Skylight"),
+        Polygon3D::new(skylight).unwrap(),
+        &glazing,
+        Some(Boundary::Space(Rc::clone(&space))),
+        None,
+    );
+
+    add_loads(&mut model, &options, &mut header, &space);
+
+    apply_handedness(&mut model, &options, 0, 0);
+
+    (model, header)
+}
+
+/// The slope of the opaque panels of [`get_sawtooth_roof_building`], in degrees
+const SAWTOOTH_PITCH: Float = 30.;
+
+/// A single space with the main wall of [`get_single_zone_test_building`](crate::get_single_zone_test_building)
+/// and a sawtooth (i.e., North-light) roof.
+///
+/// The roof spans the depth of the zone (i.e., `zone_volume / (surface_width * surface_height)`) and sits
+/// on top of the walls (at `surface_height`). It is made of `teeth` opaque panels
+/// sloped at 30 degrees that rise towards the North, each followed by a vertical
+/// glazed panel facing North. The opaque panels use the `construction` and the
+/// glazing follows the same rules as the window of the main wall.
+///
+/// The volume of the space is `zone_volume` plus the volume under the teeth.
+pub fn get_sawtooth_roof_building(
+    options: &SingleZoneTestBuildingOptions,
+    teeth: usize,
+) -> (SimpleModel, SimulationStateHeader) {
+    assert!(teeth > 0, "A sawtooth roof needs at least one tooth");
+
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let o = options.orientation;
+    let l = options.surface_width / 2.;
+    let h = options.surface_height;
+    let tooth_depth = zone_depth(options) / teeth as Float;
+    let rise = tooth_depth * SAWTOOTH_PITCH.to_radians().tan();
+
+    let volume =
+        options.zone_volume + teeth as Float * options.surface_width * tooth_depth * rise / 2.;
+    let space = add_space(&mut model, options, "Some space", volume);
+    let construction = add_construction(&mut model, options);
+    let glazing = add_window_construction(&mut model, options, &construction);
+
+    add_main_wall(
+        &mut model,
+        &mut header,
+        options,
+        &space,
+        &construction,
+        Some(&glazing),
+    );
+
+    for i in 0..teeth {
+        let y0 = i as Float * tooth_depth;
+        let y1 = y0 + tooth_depth;
+
+        // Sloped panel, facing up (and South)
+        let slope = local_loop(
+            o,
+            &[
+                (-l, y0, h),
+                (l, y0, h),
+                (l, y1, h + rise),
+                (-l, y1, h + rise),
+            ],
+        );
+        add_surface(
+            &mut model,
+            &prefixed(options, &format!("Roof panel {}", i)),
+            Polygon3D::new(slope).unwrap(),
+            &construction,
+            None,
+            Some(Boundary::Space(Rc::clone(&space))),
+        );
+
+        // Vertical glazing, facing North
+        let glass = vertical_rectangle(o, (l, y1), (-l, y1), h, h + rise);
+        add_window(
+            &mut model,
+            &mut header,
+            &prefixed(options, &format!("Roof glazing {}", i)),
+            Polygon3D::new(glass).unwrap(),
+            &glazing,
+            Some(Boundary::Space(Rc::clone(&space))),
+            None,
+        );
+    }
+
+    add_loads(&mut model, options, &mut header, &space);
+
+    apply_handedness(&mut model, options, 0, 0);
+
+    (model, header)
+}
+
+/// The depth of the sunspace of [`get_sunspace_building`], in m
+pub const SUNSPACE_DEPTH: Float = 2.;
+
+/// The fraction of the width and height of the South facade of the sunspace of
+/// [`get_sunspace_building`] that is glazed
+const SUNSPACE_GLAZING_FRACTION: Float = 0.9;
+
+/// Builds a wall of `surface_width` by `surface_height`, parallel to the main
+/// wall and `y` in front of it (i.e., towards its interior, in local coordinates), with a
+/// centred window of `window_width` by `window_height` (if both are positive) cut
+/// out of it.
+fn wall_with_window(
+    options: &SingleZoneTestBuildingOptions,
+    y: Float,
+    (window_width, window_height): (Float, Float),
+) -> (Polygon3D, Option<Loop3D>) {
+    let o = options.orientation;
+    let (w, h) = (options.surface_width, options.surface_height);
+    let mut wall = Polygon3D::new(vertical_rectangle(o, (-w / 2., y), (w / 2., y), 0., h)).unwrap();
+    if window_width <= 0.0 || window_height <= 0.0 {
+        return (wall, None);
+    }
+    assert!(
+        window_width * window_height < w * h,
+        "Win_area >= Surface_area"
+    );
+    let window = vertical_rectangle(
+        o,
+        (-window_width / 2., y),
+        (window_width / 2., y),
+        h / 2. - window_height / 2.,
+        h / 2. + window_height / 2.,
+    );
+    wall.cut_hole(window.clone()).unwrap();
+    (wall, Some(window))
+}
+
+/// A zone with a glazed sunspace (i.e., a conservatory) attached to its South.
+///
+/// The zone described by the `options` ("Some space") is separated from the
+/// "Sunspace" by a "Partition" that sits where the main wall of
+/// [`get_single_zone_test_building`](crate::get_single_zone_test_building) would be.
+/// The window described by the `options` (if any) becomes the "Partition window".
+/// The sunspace is [`SUNSPACE_DEPTH`] deep, and its "Sunspace wall" faces
+/// South with a "Sunspace glazing" covering 90% of its width and height.
+///
+/// As in the window of [`get_single_zone_test_building`](crate::get_single_zone_test_building),
+/// the front of both fenestrations is their inner side (i.e., the zone for the "Partition
+/// window" and the sunspace for the "Sunspace glazing"), and their back is the side their
+/// normal points to (i.e., the sunspace and the outdoors).
+///
+/// As with the main wall, only these two walls are modelled. The heater and the luminaire
+/// (if requested) are only added to the main zone, so the sunspace is free-running.
+pub fn get_sunspace_building(
+    options: &SingleZoneTestBuildingOptions,
+) -> (SimpleModel, SimulationStateHeader) {
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let w = options.surface_width;
+    let h = options.surface_height;
+    let d = SUNSPACE_DEPTH;
+
+    let main = add_space(&mut model, options, "Some space", options.zone_volume);
+    let sunspace = add_space(&mut model, options, "Sunspace", w * h * d);
+    let construction = add_construction(&mut model, options);
+    let glazing = add_window_construction(&mut model, options, &construction);
+
+    // Partition, facing the sunspace... with a window, if any
+    let window_size = (options.window_width, options.window_height);
+    let (p, window) = wall_with_window(options, 0., window_size);
+    if let Some(window) = window {
+        add_window(
+            &mut model,
+            &mut header,
+            &prefixed(options, "Partition window"),
+            Polygon3D::new(window).unwrap(),
+            &glazing,
+            Some(Boundary::Space(Rc::clone(&main))),
+            Some(Boundary::Space(Rc::clone(&sunspace))),
+        );
+    }
+    add_surface(
+        &mut model,
+        &prefixed(options, "Partition"),
+        p,
+        &construction,
+        Some(Boundary::Space(Rc::clone(&sunspace))),
+        Some(Boundary::Space(Rc::clone(&main))),
+    );
+
+    // Exterior facade of the sunspace, facing South
+    let glazing_size = (SUNSPACE_GLAZING_FRACTION * w, SUNSPACE_GLAZING_FRACTION * h);
+    let (p, window) = wall_with_window(options, -d, glazing_size);
+    add_surface(
+        &mut model,
+        &prefixed(options, "Sunspace wall"),
+        p,
+        &construction,
+        None,
+        Some(Boundary::Space(Rc::clone(&sunspace))),
+    );
+    add_window(
+        &mut model,
+        &mut header,
+        &prefixed(options, "Sunspace glazing"),
+        Polygon3D::new(window.unwrap()).unwrap(),
+        &glazing,
+        Some(Boundary::Space(Rc::clone(&sunspace))),
+        None,
+    );
+
+    add_loads(&mut model, options, &mut header, &main);
+
+    apply_handedness(&mut model, options, 0, 0);
+
+    (model, header)
+}
+
+/// The fraction of the width and height of both skins of
+/// [`get_double_skin_facade_building`] that is glazed
+const DOUBLE_SKIN_GLAZING_FRACTION: Float = 0.9;
+
+/// A zone behind a double-skin facade: two parallel, heavily glazed walls with
+/// a thin, tall (unconditioned) cavity between them.
+///
+/// The "Inner skin" sits where the main wall of
+/// [`get_single_zone_test_building`](crate::get_single_zone_test_building) would be,
+/// separating the zone ("Some space") from the "Cavity". The "Outer skin" is
+/// `cavity_depth` further South and faces the outdoors. Each skin is glazed
+/// ("Inner glazing" and "Outer glazing") over 90% of its width and height.
+///
+/// The window options are ignored, but the glazing follows the same construction
+/// rules as the window of [`get_single_zone_test_building`](crate::get_single_zone_test_building).
+/// As in that window, the front of both fenestrations is their inner side (i.e.,
+/// the zone for the "Inner glazing" and the cavity for the "Outer glazing"), and
+/// their back is the side their normal points to (i.e., the cavity and the outdoors).
+/// The heater and the luminaire (if requested) are only added to the zone.
+pub fn get_double_skin_facade_building(
+    options: &SingleZoneTestBuildingOptions,
+    cavity_depth: Float,
+) -> (SimpleModel, SimulationStateHeader) {
+    assert!(cavity_depth > 0.0, "A positive cavity_depth is required");
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let w = options.surface_width;
+    let h = options.surface_height;
+
+    let main = add_space(&mut model, options, "Some space", options.zone_volume);
+    let cavity = add_space(&mut model, options, "Cavity", w * h * cavity_depth);
+    let construction = add_construction(&mut model, options);
+    let glazing = add_window_construction(&mut model, options, &construction);
+
+    let glazing_size = (
+        DOUBLE_SKIN_GLAZING_FRACTION * w,
+        DOUBLE_SKIN_GLAZING_FRACTION * h,
+    );
+
+    // Inner skin, facing the cavity
+    let (p, window) = wall_with_window(options, 0., glazing_size);
+    add_surface(
+        &mut model,
+        &prefixed(options, "Inner skin"),
+        p,
+        &construction,
+        Some(Boundary::Space(Rc::clone(&cavity))),
+        Some(Boundary::Space(Rc::clone(&main))),
+    );
+    add_window(
+        &mut model,
+        &mut header,
+        &prefixed(options, "Inner glazing"),
+        Polygon3D::new(window.unwrap()).unwrap(),
+        &glazing,
+        Some(Boundary::Space(Rc::clone(&main))),
+        Some(Boundary::Space(Rc::clone(&cavity))),
+    );
+
+    // Outer skin, facing South
+    let (p, window) = wall_with_window(options, -cavity_depth, glazing_size);
+    add_surface(
+        &mut model,
+        &prefixed(options, "Outer skin"),
+        p,
+        &construction,
+        None,
+        Some(Boundary::Space(Rc::clone(&cavity))),
+    );
+    add_window(
+        &mut model,
+        &mut header,
+        &prefixed(options, "Outer glazing"),
+        Polygon3D::new(window.unwrap()).unwrap(),
+        &glazing,
+        Some(Boundary::Space(Rc::clone(&cavity))),
+        None,
+    );
+
+    add_loads(&mut model, options, &mut header, &main);
+    apply_handedness(&mut model, options, 0, 0);
+
+    (model, header)
+}
+
+/// A narrow, deep room for daylight-penetration studies: the glazed South
+/// facade is short and the room extends `depth` away from it, so daylight
+/// falls off with the distance to the window.
+///
+/// The floor area is `zone_volume / surface_height` and the width of the room
+/// (i.e., of its South wall) is `floor area / depth`, so the `surface_width` is ignored.
+/// The South wall and its window follow the rest of the `options`, as in
+/// [`get_single_zone_test_building`](crate::get_single_zone_test_building), and the room is closed by
+/// opaque "East wall", "West wall" and "North wall" surfaces facing the outdoors.
+pub fn get_deep_plan_room(
+    options: &SingleZoneTestBuildingOptions,
+    depth: Float,
+) -> (SimpleModel, SimulationStateHeader) {
+    assert!(depth > 0.0, "A positive room depth is required");
+    let options = SingleZoneTestBuildingOptions {
+        surface_width: options.zone_volume / (options.surface_height * depth),
+        ..options.clone()
+    };
+
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let space = add_space(&mut model, &options, "Some space", options.zone_volume);
+    let construction = add_construction(&mut model, &options);
+
+    add_main_wall(
+        &mut model,
+        &mut header,
+        &options,
+        &space,
+        &construction,
+        None,
+    );
+
+    let o = options.orientation;
+    let l = options.surface_width / 2.;
+    let h = options.surface_height;
+    let walls = [
+        ("East wall", (l, 0.), (l, depth)),
+        ("West wall", (-l, depth), (-l, 0.)),
+        ("North wall", (l, depth), (-l, depth)),
+    ];
+    for (name, a, b) in walls.iter() {
+        let p = Polygon3D::new(vertical_rectangle(o, *a, *b, 0., h)).unwrap();
+        add_surface(
+            &mut model,
+            &prefixed(&options, name),
+            p,
+            &construction,
+            None,
+            Some(Boundary::Space(Rc::clone(&space))),
+        );
+    }
+
+    add_loads(&mut model, &options, &mut header, &space);
+
+    apply_handedness(&mut model, &options, 0, 0);
+
+    (model, header)
+}
+
+/// An enclosed box in which every surface is (effectively) adiabatic, so the only
+/// heat flows are the internal loads (i.e., the luminaire and the heater, if requested),
+/// for checking the temperature rise they produce.
+///
+/// The box is `surface_width` wide, `zone_volume / (surface_width * surface_height)` deep
+/// and `surface_height` tall, with four walls, a "Floor" and a "Roof" made of the `construction`.
+/// `simple_model` has no adiabatic [`Boundary`], so both sides of every surface face the
+/// box itself: heat stored in the surfaces can only return to the same air. The window options
+/// and the `infiltration_rate` are ignored.
+pub fn get_adiabatic_box(
+    options: &SingleZoneTestBuildingOptions,
+) -> (SimpleModel, SimulationStateHeader) {
+    let options = SingleZoneTestBuildingOptions {
+        infiltration_rate: 0.,
+        ..options.clone()
+    };
+
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let space = add_space(&mut model, &options, "Some space", options.zone_volume);
+    let construction = add_construction(&mut model, &options);
+
+    let o = options.orientation;
+    let l = options.surface_width / 2.;
+    let h = options.surface_height;
+    let d = zone_depth(&options);
+    let surfaces = [
+        (
+            "South wall",
+            vertical_rectangle(o, (-l, 0.), (l, 0.), 0., h),
+        ),
+        ("East wall", vertical_rectangle(o, (l, 0.), (l, d), 0., h)),
+        ("North wall", vertical_rectangle(o, (l, d), (-l, d), 0., h)),
+        ("West wall", vertical_rectangle(o, (-l, d), (-l, 0.), 0., h)),
+        (
+            "Floor",
+            horizontal_rectangle(o, (-l, 0.), (l, d), 0., false),
+        ),
+        ("Roof", horizontal_rectangle(o, (-l, 0.), (l, d), h, true)),
+    ];
+    for (name, the_loop) in surfaces {
+        add_surface(
+            &mut model,
+            &prefixed(&options, name),
+            Polygon3D::new(the_loop).unwrap(),
+            &construction,
+            Some(Boundary::Space(Rc::clone(&space))),
+            Some(Boundary::Space(Rc::clone(&space))),
+        );
+    }
+
+    add_loads(&mut model, &options, &mut header, &space);
+
+    apply_handedness(&mut model, &options, 0, 0);
+
+    (model, header)
+}
+
+/// The depth of the perimeter zones of [`get_core_perimeter_floor`], in m (i.e., 15 ft)
+pub const PERIMETER_ZONE_DEPTH: Float = 4.57;
+
+/// The fractions of the width and the height of each facade of
+/// [`get_core_perimeter_floor`] that are glazed
+const PERIMETER_WINDOW_FRACTIONS: (Float, Float) = (0.9, 0.5);
+
+/// A typical floor of an office building, split into a "Core" zone and four perimeter
+/// zones ("South perimeter", "East perimeter", "North perimeter" and "West perimeter"),
+/// each [`PERIMETER_ZONE_DEPTH`] deep.
+///
+/// The floor plate is `surface_width` wide and `zone_volume / (surface_width * surface_height)`
+/// deep (so `zone_volume` is the volume of the whole floor), and it needs to be deeper and wider
+/// than two perimeter zones. Each perimeter zone has an exterior wall (e.g., "South wall")
+/// with a centred window (e.g., "South window") covering 90% of its width and 50% of its height, so
+/// the window options are ignored. The zones are separated by partitions named after the
+/// two spaces they separate (e.g., "South perimeter - Core"), whose front faces the first one.
+///
+/// The floors above and below are assumed to be identical, so the floor and ceiling
+/// of each zone (e.g., "Core floor" and "Core ceiling") face that same zone on both sides. The
+/// heater and the luminaire (if requested) are only added to the core.
+pub fn get_core_perimeter_floor(
+    options: &SingleZoneTestBuildingOptions,
+) -> (SimpleModel, SimulationStateHeader) {
+    let w = options.surface_width;
+    let h = options.surface_height;
+    let d = zone_depth(options);
+    let p = PERIMETER_ZONE_DEPTH;
+    assert!(
+        w > 2. * p && d > 2. * p,
+        "The floor plate needs to be wider and deeper than two perimeter zones"
+    );
+
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let construction = add_construction(&mut model, options);
+    let glazing = add_window_construction(&mut model, options, &construction);
+
+    // The corners of the floor plate and of the core, counterclockwise from the South-West
+    let (x0, x1) = (-w / 2., w / 2.);
+    let outer = [(x0, 0.), (x1, 0.), (x1, d), (x0, d)];
+    let inner = [(x0 + p, p), (x1 - p, p), (x1 - p, d - p), (x0 + p, d - p)];
+
+    let footprint_area = |points: &[(Float, Float)]| {
+        let n = points.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % n]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum::<Float>()
+            / 2.
+    };
+
+    // Each perimeter zone spans one side of the floor plate (counterclockwise, from the South)
+    let names = ["South", "East", "North", "West"];
+    let mut footprints: Vec<Vec<(Float, Float)>> = (0..4)
+        .map(|i| {
+            let j = (i + 1) % 4;
+            vec![outer[i], outer[j], inner[j], inner[i]]
+        })
+        .collect();
+    footprints.push(inner.to_vec());
+    let spaces: Vec<Rc<Space>> = footprints
+        .iter()
+        .enumerate()
+        .map(|(i, footprint)| {
+            let name = if i < 4 {
+                format!("{} perimeter", names[i])
+            } else {
+                "Core".to_string()
+            };
+            add_space(&mut model, options, &name, footprint_area(footprint) * h)
+        })
+        .collect();
+    let core = &spaces[4];
+
+    let o = options.orientation;
+    let (fw, fh) = PERIMETER_WINDOW_FRACTIONS;
+    for i in 0..4 {
+        let space = &spaces[i];
+        let (a, b) = (outer[i], outer[(i + 1) % 4]);
+
+        // The facade, with its window
+        let at = |t: Float| (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1));
+        let mut wall = Polygon3D::new(vertical_rectangle(o, a, b, 0., h)).unwrap();
+        let window = vertical_rectangle(
+            o,
+            at((1. - fw) / 2.),
+            at((1. + fw) / 2.),
+            h * (1. - fh) / 2.,
+            h * (1. + fh) / 2.,
+        );
+        wall.cut_hole(window.clone()).unwrap();
+        add_surface(
+            &mut model,
+            &prefixed(options, &format!("{} wall", names[i])),
+            wall,
+            &construction,
+            None,
+            Some(Boundary::Space(Rc::clone(space))),
+        );
+        add_window(
+            &mut model,
+            &mut header,
+            &prefixed(options, &format!("{} window", names[i])),
+            Polygon3D::new(window).unwrap(),
+            &glazing,
+            Some(Boundary::Space(Rc::clone(space))),
+            None,
+        );
+
+        // Towards the core
+        let (a, b) = (inner[i], inner[(i + 1) % 4]);
+        add_surface(
+            &mut model,
+            &prefixed(options, &format!("{} perimeter - Core", names[i])),
+            Polygon3D::new(vertical_rectangle(o, a, b, 0., h)).unwrap(),
+            &construction,
+            Some(Boundary::Space(Rc::clone(space))),
+            Some(Boundary::Space(Rc::clone(core))),
+        );
+
+        // Towards the previous perimeter zone, at the corner
+        let previous = (i + 3) % 4;
+        add_surface(
+            &mut model,
+            &prefixed(
+                options,
+                &format!("{} perimeter - {} perimeter", names[i], names[previous]),
+            ),
+            Polygon3D::new(vertical_rectangle(o, outer[i], inner[i], 0., h)).unwrap(),
+            &construction,
+            Some(Boundary::Space(Rc::clone(space))),
+            Some(Boundary::Space(Rc::clone(&spaces[previous]))),
+        );
+    }
+
+    // Floors and ceilings
+    for (space, footprint) in spaces.iter().zip(footprints.iter()) {
+        let ceiling: Vec<(Float, Float, Float)> =
+            footprint.iter().map(|(x, y)| (*x, *y, h)).collect();
+        let floor: Vec<(Float, Float, Float)> =
+            footprint.iter().rev().map(|(x, y)| (*x, *y, 0.)).collect();
+        for (kind, points) in [("floor", floor), ("ceiling", ceiling)] {
+            add_surface(
+                &mut model,
+                &format!("{} {}", space.name, kind),
+                Polygon3D::new(local_loop(o, &points)).unwrap(),
+                &construction,
+                Some(Boundary::Space(Rc::clone(space))),
+                Some(Boundary::Space(Rc::clone(space))),
+            );
+        }
+    }
+
+    add_loads(&mut model, options, &mut header, core);
+
+    apply_handedness(&mut model, options, 0, 0);
+
+    (model, header)
+}
+
+/// The air changes per hour of the vented attic of [`get_attic_building`]
+pub const ATTIC_AIR_CHANGES: Float = 3.;
+
+/// A zone under a flat ceiling, with an unconditioned, vented "Attic" enclosed by a
+/// gable roof above it.
+///
+/// The zone ("Some space") gets the main wall and window of
+/// [`get_single_zone_test_building`](crate::get_single_zone_test_building), and its
+/// flat "Ceiling" (at `surface_height`) is a partition whose front faces the attic.
+/// The attic spans the footprint of the zone, and its roof ("Roof - South" and "Roof - North")
+/// is sloped at `roof_pitch` degrees up to a ridge parallel to the main wall, halfway
+/// through the depth of the zone. It is closed by two triangular gables ("Gable - East" and
+/// "Gable - West"). All of these use the `construction`.
+///
+/// The attic has an infiltration of [`ATTIC_AIR_CHANGES`] (instead of `infiltration_rate`), and
+/// the heater and the luminaire (if requested) are only added to the zone.
+pub fn get_attic_building(
+    options: &SingleZoneTestBuildingOptions,
+    roof_pitch: Float,
+) -> (SimpleModel, SimulationStateHeader) {
+    assert!(
+        roof_pitch > 0.0 && roof_pitch < 90.0,
+        "The roof_pitch needs to be between 0 and 90 degrees"
+    );
+    let mut model = SimpleModel::new("The SimpleModel".to_string());
+    let mut header = SimulationStateHeader::new();
+
+    let o = options.orientation;
+    let l = options.surface_width / 2.;
+    let h = options.surface_height;
+    let d = zone_depth(options);
+    let rise = d / 2. * roof_pitch.to_radians().tan();
+
+    let space = add_space(&mut model, options, "Some space", options.zone_volume);
+    let attic_volume = options.surface_width * d * rise / 2.;
+    let attic_options = options.with_infiltration_rate(ATTIC_AIR_CHANGES * attic_volume / 3600.);
+    let attic = add_space(&mut model, &attic_options, "Attic", attic_volume);
+    let construction = add_construction(&mut model, options);
+
+    add_main_wall(
+        &mut model,
+        &mut header,
+        options,
+        &space,
+        &construction,
+        None,
+    );
+
+    // Ceiling, facing the attic
+    let p = Polygon3D::new(horizontal_rectangle(o, (-l, 0.), (l, d), h, true)).unwrap();
+    add_surface(
+        &mut model,
+        &prefixed(options, "Ceiling"),
+        p,
+        &construction,
+        Some(Boundary::Space(Rc::clone(&attic))),
+        Some(Boundary::Space(Rc::clone(&space))),
+    );
+
+    // The envelope of the attic
+    let (south, north) = gable_slopes(o, l, d, h, rise);
+    let east = local_loop(o, &[(l, 0., h), (l, d, h), (l, d / 2., h + rise)]);
+    let west = local_loop(o, &[(-l, d, h), (-l, 0., h), (-l, d / 2., h + rise)]);
+    let surfaces = [
+        ("Roof - South", south),
+        ("Roof - North", north),
+        ("Gable - East", east),
+        ("Gable - West", west),
+    ];
+    for (name, the_loop) in surfaces {
+        add_surface(
+            &mut model,
+            &prefixed(options, name),
+            Polygon3D::new(the_loop).unwrap(),
+            &construction,
+            None,
+            Some(Boundary::Space(Rc::clone(&attic))),
+        );
+    }
+
+    add_loads(&mut model, options, &mut header, &space);
+
+    apply_handedness(&mut model, options, 0, 0);
+
+    (model, header)
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use simple_model::hvac::HVAC;
+
+    #[test]
+    fn test_courtyard() {
+        let surface_width = 10.;
+        let surface_height = 3.;
+        let courtyard_ratio = 0.4;
+
+        let (model, _header) = get_courtyard_building(
+            &SingleZoneTestBuildingOptions {
+                surface_width,
+                surface_height,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            },
+            courtyard_ratio,
+        );
+
+        assert_eq!(model.spaces.len(), 1);
+        assert_eq!(model.surfaces.len(), 9);
+
+        let n_outer = model
+            .surfaces
+            .iter()
+            .filter(|s| s.name.starts_with("Outer wall"))
+            .count();
+        let n_inner = model
+            .surfaces
+            .iter()
+            .filter(|s| s.name.starts_with("Courtyard wall"))
+            .count();
+        assert_eq!(n_outer, 4);
+        assert_eq!(n_inner, 4);
+
+        // The floor has a hole
+        let floor = model.surfaces.iter().find(|s| s.name == "Floor").unwrap();
+        assert_eq!(floor.vertices.inner().len(), 1);
+        let c = courtyard_ratio * surface_width;
+        let exp_area = surface_width * surface_width - c * c;
+        let floor_area = floor.area();
+        assert!(
+            (floor_area - exp_area).abs() < 1e-3,
+            "area = {}... expecting {}",
+            floor_area,
+            exp_area
+        );
+    }
+
+    #[test]
+    fn test_buffer() {
+        let (model, _header) = get_building_with_buffer(&SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_width: 4.,
+            surface_height: 2.,
+            window_width: 1.,
+            window_height: 1.,
+            heating_power: 1500.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        assert_eq!(model.spaces.len(), 2);
+        assert_eq!(model.surfaces.len(), 3);
+        assert_eq!(model.fenestrations.len(), 1);
+
+        // Only the main space is heated
+        assert_eq!(model.hvacs.len(), 1);
+        let target = match &*model.hvacs[0] {
+            HVAC::ElectricHeater(h) => h.target_space().unwrap(),
+            _ => panic!("Expecting an ElectricHeater"),
+        };
+        assert!(Rc::ptr_eq(target, &model.spaces[0]));
+        assert_eq!(target.name, "Some space");
+        assert!(!Rc::ptr_eq(target, &model.spaces[1]));
+    }
+
+    #[test]
+    fn test_get_named_case() {
+        let (model, _header) = get_named_case("cie_reference_room").unwrap();
+        assert_eq!(model.fenestrations.len(), 1);
+
+        let (model, _header) = get_named_case("600").unwrap();
+        let (_, expected, _) = &bestest_suite()[0];
+        crate::models_equivalent(&model, expected).unwrap();
+
+        let err = get_named_case("nope").unwrap_err();
+        assert!(err.contains("cie_reference_room"), "{}", err);
+    }
+
+    #[test]
+    fn test_cie_reference_room() {
+        let (model, _header) = cie_reference_room();
+
+        let volume = model.spaces[0].volume().unwrap();
+        assert!((volume - 3.5 * 9. * 3.).abs() < 1e-6);
+
+        // The window is on the 3.5m wall
+        assert_eq!(model.surfaces.len(), 1);
+        assert_eq!(model.fenestrations.len(), 1);
+        let wall = &model.surfaces[0];
+        let window_area = model.fenestrations[0].area();
+        let gross_area = wall.area() + window_area;
+        assert!((gross_area - 3.5 * 3.).abs() < 1e-3);
+        assert!((window_area - 3.3 * 2.8).abs() < 1e-3);
+        assert!(window_area / gross_area > 0.8);
+    }
+
+    #[test]
+    fn test_sawtooth() {
+        let teeth = 3;
+        let surface_height = 3.;
+        let (model, _header) = get_sawtooth_roof_building(
+            &SingleZoneTestBuildingOptions {
+                zone_volume: 60.,
+                surface_width: 4.,
+                surface_height,
+                window_width: 1.,
+                window_height: 1.,
+                glazing: Some(crate::Glazing::Double),
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            },
+            teeth,
+        );
+
+        let panels: Vec<_> = model
+            .surfaces
+            .iter()
+            .filter(|s| s.name.starts_with("Roof panel"))
+            .collect();
+        let glazing: Vec<_> = model
+            .fenestrations
+            .iter()
+            .filter(|s| s.name.starts_with("Roof glazing"))
+            .collect();
+        assert_eq!(panels.len(), teeth);
+        assert_eq!(glazing.len(), teeth);
+
+        // All at roof level
+        for polygon in panels
+            .iter()
+            .map(|s| &s.vertices)
+            .chain(glazing.iter().map(|f| &f.vertices))
+        {
+            let outer = polygon.outer();
+            for i in 0..outer.n_vertices() {
+                assert!(outer[i].z >= surface_height - 1e-6);
+            }
+        }
+
+        // Glazing faces North, panels face up
+        for f in glazing.iter() {
+            assert!((f.vertices.normal().y - 1.).abs() < 1e-6);
+        }
+        for s in panels.iter() {
+            assert!(s.vertices.normal().z > 0.);
+        }
+
+        // The main window and the roof glazing share their construction
+        assert_eq!(model.fenestrations.len(), teeth + 1);
+        let window = &model.fenestrations[0];
+        for f in glazing.iter() {
+            assert!(Rc::ptr_eq(&window.construction, &f.construction));
+        }
+        assert_eq!(model.constructions.len(), 2);
+    }
+
+    #[test]
+    fn test_two_zone_custom() {
+        let (model, _header) = get_two_zone_custom(
+            30.,
+            75.,
+            &SingleZoneTestBuildingOptions {
+                surface_width: 3.,
+                surface_height: 2.5,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(model.spaces.len(), 2);
+        assert!((model.spaces[0].volume().unwrap() - 30.).abs() < 1e-6);
+        assert!((model.spaces[1].volume().unwrap() - 75.).abs() < 1e-6);
+        assert_eq!(model.surfaces.len(), 3);
+
+        // Both exterior walls are the same size
+        assert!((model.surfaces[0].area() - model.surfaces[1].area()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_atrium() {
+        let height = 12.;
+        let (model, _header) = get_atrium_building(
+            &SingleZoneTestBuildingOptions {
+                zone_volume: 5. * 8. * 3.,
+                surface_width: 5.,
+                surface_height: 3.,
+                construction: vec![TestMat::Concrete(0.2)],
+                glazing: Some(crate::Glazing::Double),
+                ..Default::default()
+            },
+            height,
+        );
+
+        assert!((model.spaces[0].volume().unwrap() - 5. * 8. * height).abs() < 1e-6);
+
+        let window = model
+            .fenestrations
+            .iter()
+            .find(|f| f.name == "window one")
+            .unwrap();
+        let wall = model.surfaces.iter().find(|s| s.name == "Surface").unwrap();
+        let wwr = window.area() / (window.area() + wall.area());
+        assert!(wwr > 0.8, "wwr = {}", wwr);
+        assert!(window.vertices.normal().y < -0.99);
+
+        let skylight = model
+            .fenestrations
+            .iter()
+            .find(|f| f.name == "Skylight")
+            .unwrap();
+        assert!(skylight.vertices.normal().z > 0.99);
+        assert!((skylight.area() - 0.25 * 5. * 8.).abs() < 1e-6);
+
+        // The window and the skylight share their construction
+        assert!(Rc::ptr_eq(&window.construction, &skylight.construction));
+        assert_eq!(model.constructions.len(), 2);
+    }
+
+    #[test]
+    fn test_sunspace() {
+        let (model, _header) = get_sunspace_building(&SingleZoneTestBuildingOptions {
+            zone_volume: 4. * 6. * 3.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 2.,
+            window_height: 1.5,
+            heating_power: 1000.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        assert_eq!(model.spaces.len(), 2);
+        let (main, sunspace) = (&model.spaces[0], &model.spaces[1]);
+        assert_eq!(sunspace.name, "Sunspace");
+        let exp_volume = 4. * 3. * SUNSPACE_DEPTH;
+        assert!((sunspace.volume().unwrap() - exp_volume).abs() < 1e-6);
+
+        // Shared, partly glazed partition
+        let partition = model
+            .surfaces
+            .iter()
+            .find(|s| s.name == "Partition")
+            .unwrap();
+        match (partition.front_boundary(), partition.back_boundary()) {
+            (Ok(Boundary::Space(f)), Ok(Boundary::Space(b))) => {
+                assert!(Rc::ptr_eq(f, sunspace));
+                assert!(Rc::ptr_eq(b, main));
+            }
+            _ => panic!("Expecting the partition to be between the two spaces"),
+        }
+        assert!((partition.area() - (12. - 3.)).abs() < 1e-6);
+        let window = model
+            .fenestrations
+            .iter()
+            .find(|f| f.name == "Partition window")
+            .unwrap();
+        match (window.front_boundary(), window.back_boundary()) {
+            (Ok(Boundary::Space(f)), Ok(Boundary::Space(b))) => {
+                assert!(Rc::ptr_eq(f, main));
+                assert!(Rc::ptr_eq(b, sunspace));
+            }
+            _ => panic!("Expecting the partition window to be between the two spaces"),
+        }
+
+        // Heavily glazed exterior facade
+        let glazing = model
+            .fenestrations
+            .iter()
+            .find(|f| f.name == "Sunspace glazing")
+            .unwrap();
+        assert!(glazing.vertices.normal().y < -0.99);
+        assert!((glazing.area() - 0.81 * 12.).abs() < 1e-6 * 0.81 * 12.);
+        assert!(glazing.back_boundary().is_err());
+        let glazing_front = glazing.front_boundary().unwrap();
+        assert!(matches!(glazing_front, Boundary::Space(s) if Rc::ptr_eq(s, sunspace)));
+
+        // The sunspace is free-running
+        assert_eq!(model.hvacs.len(), 1);
+        match &*model.hvacs[0] {
+            HVAC::ElectricHeater(h) => assert!(Rc::ptr_eq(h.target_space().unwrap(), main)),
+            _ => panic!("Expecting an ElectricHeater"),
+        }
+    }
+
+    #[test]
+    fn test_courtyard_flip_handedness() {
+        let w = 10.;
+        let (model, _header) = get_courtyard_building(
+            &SingleZoneTestBuildingOptions {
+                surface_width: w,
+                surface_height: 3.,
+                flip_handedness: true,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            },
+            0.4,
+        );
+
+        // Outer walls face away from the centre, courtyard walls face it
+        for s in model.surfaces.iter().filter(|s| s.name.contains("wall")) {
+            let (centroid, normal) = crate::surface_geometry(&model, &s.name).unwrap();
+            let out = centroid.x * normal.x + (centroid.y - w / 2.) * normal.y;
+            if s.name.starts_with("Outer wall") {
+                assert!(out > 0., "{} faces inwards", s.name);
+            } else {
+                assert!(out < 0., "{} faces outwards", s.name);
+            }
+        }
+
+        // What was East is now West
+        let east = model
+            .surfaces
+            .iter()
+            .find(|s| s.name == "Outer wall East")
+            .unwrap();
+        assert!(east.vertices.normal().x < -0.99);
+
+        // The floor still faces down
+        let floor = model.surfaces.iter().find(|s| s.name == "Floor").unwrap();
+        assert!(floor.vertices.normal().z < -0.99);
+    }
+
+    #[test]
+    fn test_double_skin_facade() {
+        let cavity_depth = 0.6;
+        let (model, _header) = get_double_skin_facade_building(
+            &SingleZoneTestBuildingOptions {
+                zone_volume: 4. * 6. * 10.,
+                surface_width: 4.,
+                surface_height: 10.,
+                window_uses_wall_construction: false,
+                window_construction: vec![
+                    TestMat::Glass(0.006, 0.8),
+                    TestMat::Air(0.012),
+                    TestMat::Glass(0.006, 0.8),
+                ],
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            },
+            cavity_depth,
+        );
+
+        assert_eq!(model.spaces.len(), 2);
+        let (main, cavity) = (&model.spaces[0], &model.spaces[1]);
+        assert_eq!(cavity.name, "Cavity");
+        assert!((cavity.volume().unwrap() - 4. * 10. * cavity_depth).abs() < 1e-6);
+
+        let find = |name: &str| model.fenestrations.iter().find(|f| f.name == name).unwrap();
+        let (inner, outer) = (find("Inner glazing"), find("Outer glazing"));
+
+        // Parallel, both facing South, and cavity_depth apart
+        assert!(inner.vertices.normal().y < -0.99);
+        assert!(outer.vertices.normal().y < -0.99);
+        let gap = inner.vertices.outer()[0].y - outer.vertices.outer()[0].y;
+        assert!((gap - cavity_depth).abs() < 1e-6, "gap = {}", gap);
+        assert!((inner.area() - 0.81 * 40.).abs() < 1e-6 * 0.81 * 40.);
+        assert_eq!(inner.construction.materials.len(), 3);
+
+        // The cavity is between them
+        match (inner.front_boundary(), inner.back_boundary()) {
+            (Ok(Boundary::Space(f)), Ok(Boundary::Space(b))) => {
+                assert!(Rc::ptr_eq(f, main));
+                assert!(Rc::ptr_eq(b, cavity));
+            }
+            _ => panic!("Expecting the inner glazing to be between the two spaces"),
+        }
+        assert!(outer.back_boundary().is_err());
+        let outer_front = outer.front_boundary().unwrap();
+        assert!(matches!(outer_front, Boundary::Space(s) if Rc::ptr_eq(s, cavity)));
+    }
+
+    #[test]
+    fn test_deep_plan_room() {
+        let (model, _header) = get_deep_plan_room(
+            &SingleZoneTestBuildingOptions {
+                zone_volume: 3. * 12. * 3.,
+                surface_height: 3.,
+                window_width: 2.,
+                window_height: 1.5,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            },
+            12.,
+        );
+
+        assert_eq!(model.surfaces.len(), 4);
+        let (min, max) = crate::bounding_box(&model);
+        let (width, depth) = (max.x - min.x, max.y - min.y);
+        assert!((width - 3.).abs() < 1e-6, "width = {}", width);
+        assert!(
+            (depth / width - 4.).abs() < 1e-6,
+            "ratio = {}",
+            depth / width
+        );
+
+        // The window is on the short, South facade
+        assert_eq!(model.fenestrations.len(), 1);
+        let window = &model.fenestrations[0];
+        assert!(window.vertices.normal().y < -0.99);
+        let south = model.surfaces.iter().find(|s| s.name == "Surface").unwrap();
+        let north = model
+            .surfaces
+            .iter()
+            .find(|s| s.name == "North wall")
+            .unwrap();
+        let east = model
+            .surfaces
+            .iter()
+            .find(|s| s.name == "East wall")
+            .unwrap();
+        assert!(north.vertices.normal().y > 0.99);
+        assert!(east.vertices.normal().x > 0.99);
+        assert!((south.area() + window.area() - 9.).abs() < 1e-6);
+        assert!(south.area() + window.area() < east.area());
+    }
+
+    #[test]
+    fn test_bestest_suite() {
+        let suite = bestest_suite();
+        let names: Vec<&str> = suite.iter().map(|(n, _, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["600", "600FF", "610", "900", "900FF", "910"]);
+
+        let (_, light, _) = &suite[0];
+        let (_, free_float, _) = &suite[1];
+        let (_, shaded, _) = &suite[2];
+        let (_, heavy, _) = &suite[3];
+        assert_eq!(light.hvacs.len(), 1);
+        assert!(free_float.hvacs.is_empty());
+
+        // 12 m2 of glazing on the South wall
+        let glazed: Float = light.fenestrations.iter().map(|f| f.area()).sum();
+        assert!((glazed - 12.).abs() < 1e-6, "glazed = {}", glazed);
+
+        // 600 and 900 only differ in their wall construction
+        assert!(crate::models_equivalent(light, heavy).is_err());
+        assert_eq!(light.surfaces.len(), heavy.surfaces.len());
+        for (a, b) in light.surfaces.iter().zip(heavy.surfaces.iter()) {
+            assert_eq!(a.name, b.name);
+            assert!((a.area() - b.area()).abs() < 1e-9);
+        }
+        let r =
+            |model: &SimpleModel| crate::construction_r_value(model, "the construction").unwrap();
+        assert!((r(light) - r(heavy)).abs() > 1e-3);
+        let heavy_layers: Vec<&str> = heavy.constructions[0]
+            .materials
+            .iter()
+            .map(|m| match &*m.substance {
+                simple_model::substance::Substance::Normal(s) => s.name.as_str(),
+                _ => panic!("Expecting Normal substances"),
+            })
+            .collect();
+        assert_eq!(
+            heavy_layers,
+            vec!["wood siding", "foam insulation", "concrete block"]
+        );
+        assert!(
+            (light.spaces[0].volume().unwrap() - heavy.spaces[0].volume().unwrap()).abs() < 1e-9
+        );
+
+        // 610 adds an 8 m x 1 m overhang to 600
+        assert_eq!(shaded.surfaces.len(), light.surfaces.len() + 1);
+        let overhang = shaded
+            .surfaces
+            .iter()
+            .find(|s| s.name == "Overhang")
+            .unwrap();
+        assert!((overhang.area() - 8.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_adiabatic_box() {
+        let (model, _header) = get_adiabatic_box(&SingleZoneTestBuildingOptions {
+            zone_volume: 4. * 5. * 3.,
+            surface_width: 4.,
+            surface_height: 3.,
+            window_width: 1.,
+            window_height: 1.,
+            infiltration_rate: 0.1,
+            lighting_power: 300.,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        assert_eq!(model.surfaces.len(), 6);
+        assert!(model.fenestrations.is_empty());
+        let space = &model.spaces[0];
+        for s in model.surfaces.iter() {
+            for boundary in [s.front_boundary(), s.back_boundary()] {
+                match boundary {
+                    Ok(Boundary::Space(b)) => assert!(Rc::ptr_eq(b, space)),
+                    _ => panic!("{} is not adiabatic", s.name),
+                }
+            }
+        }
+        let area: Float = model.surfaces.iter().map(|s| s.area()).sum();
+        assert!((area - 2. * (4. * 5. + 4. * 3. + 5. * 3.)).abs() < 1e-6);
+
+        // Only the internal loads remain
+        assert_eq!(model.luminaires.len(), 1);
+        assert!(crate::total_ua(&model).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_core_perimeter_floor() {
+        let (w, d, h) = (30., 20., 3.);
+        let (model, _header) = get_core_perimeter_floor(&SingleZoneTestBuildingOptions {
+            zone_volume: w * d * h,
+            surface_width: w,
+            surface_height: h,
+            construction: vec![TestMat::Concrete(0.2)],
+            ..Default::default()
+        });
+
+        assert_eq!(model.spaces.len(), 5);
+        let volume: Float = model.spaces.iter().map(|s| s.volume().unwrap()).sum();
+        assert!(
+            (volume - w * d * h).abs() < 1e-6 * w * d * h,
+            "volume = {}",
+            volume
+        );
+        let core = &model.spaces[4];
+        assert_eq!(core.name, "Core");
+        let p = PERIMETER_ZONE_DEPTH;
+        let exp = (w - 2. * p) * (d - 2. * p) * h;
+        assert!((core.volume().unwrap() - exp).abs() < 1e-6 * exp);
+
+        // Each perimeter zone has exterior glazing on its own facade; the core has none
+        let glazed_spaces: Vec<&str> = model
+            .fenestrations
+            .iter()
+            .map(|f| match (f.front_boundary(), f.back_boundary()) {
+                (Ok(Boundary::Space(s)), Err(_)) => s.name.as_str(),
+                _ => panic!("{} is not exterior glazing", f.name),
+            })
+            .collect();
+        assert_eq!(
+            glazed_spaces,
+            vec![
+                "South perimeter",
+                "East perimeter",
+                "North perimeter",
+                "West perimeter"
+            ]
+        );
+        let azimuths: Vec<Float> = model
+            .fenestrations
+            .iter()
+            .map(|f| crate::geometry::azimuth(f.vertices.normal()).round())
+            .collect();
+        assert_eq!(azimuths, vec![180., 90., 0., 270.]);
+
+        // The core only touches perimeter zones (and itself)
+        for s in model.surfaces.iter() {
+            let touches_core = [s.front_boundary(), s.back_boundary()]
+                .iter()
+                .any(|b| matches!(b, Ok(Boundary::Space(x)) if Rc::ptr_eq(x, core)));
+            if touches_core {
+                assert!(
+                    s.front_boundary().is_ok() && s.back_boundary().is_ok(),
+                    "{}",
+                    s.name
+                );
+            }
+        }
+        let partition = model
+            .surfaces
+            .iter()
+            .find(|s| s.name == "South perimeter - Core")
+            .unwrap();
+        assert!(partition.vertices.normal().y < -0.99);
+        assert!(model
+            .surfaces
+            .iter()
+            .any(|s| s.name == "East perimeter - South perimeter"));
+    }
+
+    #[test]
+    fn test_attic_building() {
+        let (model, _header) = get_attic_building(
+            &SingleZoneTestBuildingOptions {
+                zone_volume: 4. * 6. * 3.,
+                surface_width: 4.,
+                surface_height: 3.,
+                window_width: 1.,
+                window_height: 1.,
+                heating_power: 1000.,
+                construction: vec![TestMat::Concrete(0.2)],
+                ..Default::default()
+            },
+            45.,
+        );
+
+        assert_eq!(model.spaces.len(), 2);
+        let (main, attic) = (&model.spaces[0], &model.spaces[1]);
+        assert_eq!(attic.name, "Attic");
+        // A 45 degree roof over a 6 m deep zone rises 3 m
+        assert!((attic.volume().unwrap() - 4. * 6. * 3. / 2.).abs() < 1e-6);
+        assert!(matches!(
+            attic.infiltration(),
+            Ok(simple_model::Infiltration::Constant(_))
+        ));
+
+        let ceiling = model.surfaces.iter().find(|s| s.name == "Ceiling").unwrap();
+        assert!(ceiling.vertices.normal().z > 0.99);
+        match (ceiling.front_boundary(), ceiling.back_boundary()) {
+            (Ok(Boundary::Space(f)), Ok(Boundary::Space(b))) => {
+                assert!(Rc::ptr_eq(f, attic));
+                assert!(Rc::ptr_eq(b, main));
+            }
+            _ => panic!("Expecting the ceiling to be between the two spaces"),
+        }
+
+        for name in ["Roof - South", "Roof - North"] {
+            let roof = model.surfaces.iter().find(|s| s.name == name).unwrap();
+            let n = roof.vertices.normal();
+            assert!(
+                (n.z - (45. as Float).to_radians().cos()).abs() < 1e-6,
+                "{:?}",
+                n
+            );
+            assert!(matches!(roof.back_boundary(), Ok(Boundary::Space(s)) if Rc::ptr_eq(s, attic)));
+            assert!(roof.front_boundary().is_err());
+        }
+        let gable = model
+            .surfaces
+            .iter()
+            .find(|s| s.name == "Gable - West")
+            .unwrap();
+        assert!((gable.area() - 6. * 3. / 2.).abs() < 1e-6);
+        assert!(gable.vertices.normal().x < -0.99);
+
+        // The attic is unconditioned
+        assert_eq!(model.hvacs.len(), 1);
+    }
+
+    #[test]
+    fn test_effective_options() {
+        // Every case has its options
+        for (name, _) in NAMED_CASES {
+            assert!(effective_options(name).is_ok(), "{}", name);
+        }
+
+        let cie = effective_options("cie_reference_room").unwrap();
+        assert!((cie.zone_volume - 3.5 * 9. * 3.).abs() < 1e-6);
+        assert!((cie.surface_width - 3.5).abs() < 1e-9);
+        assert!((cie.surface_height - 3.).abs() < 1e-9);
+        assert!((cie.window_width - 3.3).abs() < 1e-9);
+        assert!((cie.window_height - 2.8).abs() < 1e-9);
+        assert!(
+            matches!(cie.construction.as_slice(), [TestMat::Concrete(t)] if (*t - 0.2).abs() < 1e-9)
+        );
+        assert!(cie.heating_power.abs() < 1e-9);
+
+        // Name and thickness of each layer
+        let layers = |options: &SingleZoneTestBuildingOptions| -> Vec<(String, Float)> {
+            options
+                .custom_construction
+                .as_ref()
+                .unwrap()
+                .iter()
+                .map(|(s, t)| (s.name.clone(), *t))
+                .collect()
+        };
+        let bestest = effective_options("600").unwrap();
+        assert!((bestest.zone_volume - 8. * 6. * 2.7).abs() < 1e-4);
+        assert!((bestest.surface_width - 8.).abs() < 1e-9);
+        assert!((bestest.surface_height - 2.7).abs() < 1e-9);
+        assert!((bestest.heating_power - 3000.).abs() < 1e-9);
+        assert!((bestest.lighting_power - 200.).abs() < 1e-9);
+        assert!((bestest.infiltration_rate - 0.5 * 8. * 6. * 2.7 / 3600.).abs() < 1e-6);
+        assert_eq!(bestest.glazing, Some(crate::Glazing::Double));
+        assert!(bestest.overhang_depth.is_none());
+        assert_eq!(
+            layers(&bestest),
+            vec![
+                ("wood siding".to_string(), 0.009),
+                ("fiberglass quilt".to_string(), 0.066),
+                ("plasterboard".to_string(), 0.012),
+            ]
+        );
+
+        let heavy = effective_options("900FF").unwrap();
+        assert!(heavy.heating_power.abs() < 1e-9);
+        assert_eq!(
+            layers(&heavy),
+            vec![
+                ("wood siding".to_string(), 0.009),
+                ("foam insulation".to_string(), 0.0615),
+                ("concrete block".to_string(), 0.1),
+            ]
+        );
+        assert_eq!(effective_options("610").unwrap().overhang_depth, Some(1.));
+
+        match effective_options("nope") {
+            Err(e) => assert!(e.contains("900FF"), "{}", e),
+            Ok(_) => panic!("Expecting an unknown case"),
+        }
+    }
+}